@@ -0,0 +1,500 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::cells::Cells;
+use crate::neighbourhood::Neighbourhood;
+
+pub type State = u8;
+
+/// A cell's (y, x)
+pub type Coordinate = (isize, isize);
+
+/// The (height, width) and cells of a generation's live rectangle, for spotting repeats
+type Pattern = (usize, usize, Cells);
+
+/// Given a cell's state and its neighbours' states (in the neighbourhood's offset order), returns
+/// the cell's state in the next generation
+pub type Rule = Rc<dyn Fn(State, &[State]) -> State>;
+
+/// Given a cell's state and how many of its neighbours are in each state (indexed by state),
+/// returns the cell's state in the next generation
+pub type CountRule = Rc<dyn Fn(State, &[usize]) -> State>;
+
+/// What a rule gets to see of the neighbourhood
+#[derive(Clone)]
+enum Update {
+    Window(Rule),
+    Counts(CountRule),
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Boundary {
+    /// Cells are confined to the starting rectangle, and anything outside it reads as state 0
+    Fixed,
+    /// The rectangle grows and shrinks to fit the cells that aren't in state 0.  The rule must
+    /// leave a cell surrounded by state 0 in state 0.
+    Growing,
+}
+
+/// A generation whose cells match an earlier generation's
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Repeat {
+    /// The earlier generation
+    pub start: usize,
+    /// Number of generations between the two
+    pub period: usize,
+    /// How far (y, x) the cells moved in that time: (0, 0) for a cycle, anything else for a glider
+    pub shift: Coordinate,
+}
+
+#[derive(Clone)]
+pub struct Automaton {
+    pub generation: usize,
+    states: usize,
+    origin: Coordinate,
+    height: usize,
+    width: usize,
+    cells: Cells,
+    neighbourhood: Neighbourhood,
+    boundary: Boundary,
+    rule: Update,
+}
+
+impl Automaton {
+    /// Builds an automaton from rows of cell states, with the first cell of the first row at (0, 0)
+    pub fn new(
+        rows: &[Vec<State>],
+        states: usize,
+        neighbourhood: Neighbourhood,
+        boundary: Boundary,
+        rule: Rule,
+    ) -> Automaton {
+        Automaton::build(rows, states, neighbourhood, boundary, Update::Window(rule))
+    }
+
+    /// Builds an automaton whose rule only cares how many neighbours are in each state, not which
+    /// ones.  Cheaper than #new for big neighbourhoods since no window is handed to the rule.
+    pub fn totalistic(
+        rows: &[Vec<State>],
+        states: usize,
+        neighbourhood: Neighbourhood,
+        boundary: Boundary,
+        rule: CountRule,
+    ) -> Automaton {
+        Automaton::build(rows, states, neighbourhood, boundary, Update::Counts(rule))
+    }
+
+    fn build(
+        rows: &[Vec<State>],
+        states: usize,
+        neighbourhood: Neighbourhood,
+        boundary: Boundary,
+        rule: Update,
+    ) -> Automaton {
+        let height = rows.len();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut cells = Cells::new(states, height * width);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, state) in row.iter().enumerate() {
+                cells.set(y * width + x, *state);
+            }
+        }
+
+        Automaton {
+            generation: 0,
+            states,
+            origin: (0, 0),
+            height,
+            width,
+            cells,
+            neighbourhood,
+            boundary,
+            rule,
+        }
+    }
+
+    /// The (y, x) of the top left cell
+    pub fn origin(&self) -> Coordinate {
+        self.origin
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get(&self, y: isize, x: isize) -> State {
+        match self.index(y, x) {
+            Some(i) => self.cells.get(i),
+            None => 0,
+        }
+    }
+
+    /// Sets a cell's state.  Growing automata grow to fit the cell, fixed ones panic if it's out
+    /// of bounds.
+    pub fn set(&mut self, y: isize, x: isize, state: State) {
+        if self.index(y, x).is_none() {
+            assert_eq!(self.boundary, Boundary::Growing, "({}, {}) is out of bounds", y, x);
+            let min_y = self.origin.0.min(y);
+            let min_x = self.origin.1.min(x);
+            let max_y = (self.origin.0 + self.height as isize - 1).max(y);
+            let max_x = (self.origin.1 + self.width as isize - 1).max(x);
+            self.resize((min_y, min_x), (max_y - min_y + 1) as usize, (max_x - min_x + 1) as usize);
+        }
+
+        let i = self.index(y, x).unwrap();
+        self.cells.set(i, state);
+    }
+
+    /// Every cell in the rectangle, in reading order
+    pub fn iter(&self) -> impl Iterator<Item = (Coordinate, State)> + '_ {
+        (0..self.height * self.width).map(move |i| {
+            let y = self.origin.0 + (i / self.width) as isize;
+            let x = self.origin.1 + (i % self.width) as isize;
+            ((y, x), self.cells.get(i))
+        })
+    }
+
+    /// Number of cells in the rectangle in the given state
+    pub fn count(&self, state: State) -> usize {
+        self.cells.count(state)
+    }
+
+    /// Advances one generation
+    pub fn step(&mut self) {
+        let (reach_y, reach_x) = self.neighbourhood.reach();
+        let (origin, height, width) = match self.boundary {
+            Boundary::Fixed => (self.origin, self.height, self.width),
+            Boundary::Growing => match self.live_bounds() {
+                Some(((min_y, min_x), (max_y, max_x))) => (
+                    (min_y - reach_y as isize, min_x - reach_x as isize),
+                    (max_y - min_y + 1) as usize + 2 * reach_y,
+                    (max_x - min_x + 1) as usize + 2 * reach_x,
+                ),
+                None => (self.origin, 0, 0),
+            },
+        };
+
+        // Unpack the cells into a byte each, with a margin of state 0 around the new rectangle wide
+        // enough that every neighbour is a plain index into it
+        let padded_width = width + 2 * reach_x;
+        let top = origin.0 - reach_y as isize;
+        let left = origin.1 - reach_x as isize;
+        let mut padded = vec![0; (height + 2 * reach_y) * padded_width];
+
+        for ((y, x), state) in self.iter() {
+            let row = y - top;
+            let col = x - left;
+            if row >= 0 && col >= 0 && col < padded_width as isize {
+                if let Some(cell) = padded.get_mut(row as usize * padded_width + col as usize) {
+                    *cell = state;
+                }
+            }
+        }
+
+        // Each offset as an index from the top left corner of a cell's neighbourhood
+        let offsets = self.neighbourhood
+            .offsets
+            .iter()
+            .map(|(dy, dx)| {
+                (dy + reach_y as isize) as usize * padded_width + (dx + reach_x as isize) as usize
+            })
+            .collect::<Vec<_>>();
+        let centre = reach_y * padded_width + reach_x;
+
+        let mut next = Cells::new(self.states, height * width);
+        let mut neighbours = vec![0; offsets.len()];
+        let mut counts = vec![0; self.states];
+
+        for row in 0..height {
+            for col in 0..width {
+                let corner = row * padded_width + col;
+                let state = padded[corner + centre];
+
+                let next_state = match &self.rule {
+                    Update::Window(rule) => {
+                        for (neighbour, offset) in neighbours.iter_mut().zip(&offsets) {
+                            *neighbour = padded[corner + offset];
+                        }
+                        rule(state, &neighbours)
+                    },
+                    Update::Counts(rule) => {
+                        counts.iter_mut().for_each(|count| *count = 0);
+                        for offset in &offsets {
+                            counts[padded[corner + offset] as usize] += 1;
+                        }
+                        rule(state, &counts)
+                    },
+                };
+                next.set(row * width + col, next_state);
+            }
+        }
+
+        self.origin = origin;
+        self.height = height;
+        self.width = width;
+        self.cells = next;
+        self.generation += 1;
+    }
+
+    /// Steps until the cells repeat an earlier generation, either in place (a cycle) or shifted
+    /// (a glider, which only growing automata can have).  Gives up after max_generations.
+    ///
+    /// Only a hash of each generation is kept, so big automata can run for a long time before
+    /// repeating.  A matching hash is checked by replaying from the first generation.
+    pub fn run_until_repeat(&mut self, max_generations: usize) -> Option<Repeat> {
+        let first = self.clone();
+        let mut seen: HashMap<u64, (usize, Coordinate)> = HashMap::new();
+        let last_generation = self.generation + max_generations;
+
+        loop {
+            let (origin, pattern) = self.pattern();
+            let mut hasher = DefaultHasher::new();
+            pattern.hash(&mut hasher);
+            let hash = hasher.finish();
+
+            if let Some(&(start, start_origin)) = seen.get(&hash) {
+                let mut earlier = first.clone();
+                while earlier.generation < start {
+                    earlier.step();
+                }
+
+                if earlier.pattern().1 == pattern {
+                    return Some(Repeat {
+                        start,
+                        period: self.generation - start,
+                        shift: (origin.0 - start_origin.0, origin.1 - start_origin.1),
+                    });
+                }
+            }
+            seen.insert(hash, (self.generation, origin));
+
+            if self.generation >= last_generation {
+                return None;
+            }
+            self.step();
+        }
+    }
+
+    /// Jumps ahead to the given generation using a repeat found by #run_until_repeat, only
+    /// stepping through the generations left over after skipping whole periods
+    pub fn skip_to(&mut self, generation: usize, repeat: &Repeat) {
+        assert!(self.generation >= repeat.start, "Can't skip from before the repeat started");
+
+        if generation > self.generation {
+            let periods = (generation - self.generation) / repeat.period;
+            self.origin.0 += periods as isize * repeat.shift.0;
+            self.origin.1 += periods as isize * repeat.shift.1;
+            self.generation += periods * repeat.period;
+        }
+
+        while self.generation < generation {
+            self.step();
+        }
+    }
+
+    fn index(&self, y: isize, x: isize) -> Option<usize> {
+        let row = y - self.origin.0;
+        let col = x - self.origin.1;
+
+        if row < 0 || col < 0 || row >= self.height as isize || col >= self.width as isize {
+            None
+        } else {
+            Some(row as usize * self.width + col as usize)
+        }
+    }
+
+    fn resize(&mut self, origin: Coordinate, height: usize, width: usize) {
+        let mut cells = Cells::new(self.states, height * width);
+
+        for ((y, x), state) in self.iter() {
+            let row = (y - origin.0) as usize;
+            let col = (x - origin.1) as usize;
+            if row < height && col < width {
+                cells.set(row * width + col, state);
+            }
+        }
+
+        self.origin = origin;
+        self.height = height;
+        self.width = width;
+        self.cells = cells;
+    }
+
+    /// The smallest rectangle holding every cell that isn't in state 0, as (min, max) corners
+    fn live_bounds(&self) -> Option<(Coordinate, Coordinate)> {
+        self.iter()
+            .filter(|(_coord, state)| *state != 0)
+            .fold(None, |bounds, ((y, x), _state)| match bounds {
+                None => Some(((y, x), (y, x))),
+                Some(((min_y, min_x), (max_y, max_x))) => {
+                    Some(((min_y.min(y), min_x.min(x)), (max_y.max(y), max_x.max(x))))
+                },
+            })
+    }
+
+    /// The cells to compare when looking for repeats.  Fixed automata compare every cell, growing
+    /// ones compare the live cells' rectangle wherever it is.
+    fn pattern(&self) -> (Coordinate, Pattern) {
+        if self.boundary == Boundary::Fixed {
+            return (self.origin, (self.height, self.width, self.cells.clone()));
+        }
+
+        match self.live_bounds() {
+            None => ((0, 0), (0, 0, Cells::new(self.states, 0))),
+            Some(((min_y, min_x), (max_y, max_x))) => {
+                let mut trimmed = self.clone();
+                trimmed.resize(
+                    (min_y, min_x),
+                    (max_y - min_y + 1) as usize,
+                    (max_x - min_x + 1) as usize,
+                );
+                (trimmed.origin, (trimmed.height, trimmed.width, trimmed.cells))
+            },
+        }
+    }
+}
+
+impl fmt::Debug for Automaton {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Generation {} at {:?}:", self.generation, self.origin)?;
+        for row in 0..self.height {
+            for col in 0..self.width {
+                write!(f, "{}", self.cells.get(row * self.width + col))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn life(rows: &[&str], boundary: Boundary) -> Automaton {
+        let rows = rows
+            .iter()
+            .map(|row| row.chars().map(|c| if c == '#' { 1 } else { 0 }).collect())
+            .collect::<Vec<_>>();
+        let rule: Rule = Rc::new(|cell, neighbours| {
+            let alive = neighbours.iter().filter(|n| **n == 1).count();
+            match (cell, alive) {
+                (1, 2) | (1, 3) | (0, 3) => 1,
+                _ => 0,
+            }
+        });
+        Automaton::new(&rows, 2, Neighbourhood::moore(), boundary, rule)
+    }
+
+    #[test]
+    fn test_automaton_get_set() {
+        let mut fixed = life(&["...", ".#."], Boundary::Fixed);
+        assert_eq!(fixed.get(1, 1), 1);
+        assert_eq!(fixed.get(-1, 5), 0);
+        fixed.set(0, 2, 1);
+        assert_eq!(fixed.count(1), 2);
+
+        let mut growing = life(&["#"], Boundary::Growing);
+        growing.set(-2, 3, 1);
+        assert_eq!(growing.origin(), (-2, 0));
+        assert_eq!((growing.height(), growing.width()), (3, 4));
+        assert_eq!(growing.get(0, 0), 1);
+        assert_eq!(growing.get(-2, 3), 1);
+        assert_eq!(growing.count(1), 2);
+    }
+
+    #[test]
+    fn test_automaton_step() {
+        let mut blinker = life(&[".....", "..#..", "..#..", "..#..", "....."], Boundary::Fixed);
+        blinker.step();
+        assert_eq!(blinker.generation, 1);
+        assert_eq!(
+            blinker.iter().filter(|(_c, s)| *s == 1).map(|(c, _s)| c).collect::<Vec<_>>(),
+            vec![(2, 1), (2, 2), (2, 3)],
+        );
+
+        // Cells on the edge of a fixed automaton only see the neighbours inside it
+        let mut corner = life(&["##", "#."], Boundary::Fixed);
+        corner.step();
+        assert_eq!(corner.count(1), 4);
+        corner.step();
+        assert_eq!(corner.count(1), 4);
+    }
+
+    #[test]
+    fn test_automaton_totalistic() {
+        // Life again, but counting neighbours instead of looking at them
+        let rule: CountRule = Rc::new(|cell, counts| match (cell, counts[1]) {
+            (1, 2) | (1, 3) | (0, 3) => 1,
+            _ => 0,
+        });
+        let rows = ["#..#.#", ".##..#", "#.#.##", "...#..", "##.#.#"]
+            .iter()
+            .map(|row| row.chars().map(|c| if c == '#' { 1 } else { 0 }).collect())
+            .collect::<Vec<_>>();
+
+        for boundary in &[Boundary::Fixed, Boundary::Growing] {
+            let mut counted =
+                Automaton::totalistic(&rows, 2, Neighbourhood::moore(), *boundary, rule.clone());
+            let mut windowed = life(&["#..#.#", ".##..#", "#.#.##", "...#..", "##.#.#"], *boundary);
+
+            for _ in 0..10 {
+                counted.step();
+                windowed.step();
+                assert_eq!(counted.pattern(), windowed.pattern());
+            }
+        }
+    }
+
+    #[test]
+    fn test_automaton_run_until_repeat() {
+        let mut blinker = life(&[".....", "..#..", "..#..", "..#..", "....."], Boundary::Fixed);
+        assert_eq!(
+            blinker.run_until_repeat(10),
+            Some(Repeat { start: 0, period: 2, shift: (0, 0) }),
+        );
+
+        let mut glider = life(&[".#.", "..#", "###"], Boundary::Growing);
+        assert_eq!(
+            glider.run_until_repeat(10),
+            Some(Repeat { start: 0, period: 4, shift: (1, 1) }),
+        );
+
+        let mut glider = life(&[".#.", "..#", "###"], Boundary::Growing);
+        assert_eq!(glider.run_until_repeat(2), None);
+        assert_eq!(glider.generation, 2);
+    }
+
+    #[test]
+    fn test_automaton_skip_to() {
+        // Every plant moves one cell to the right each generation
+        let rule: Rule = Rc::new(|_cell, neighbours| neighbours[0]);
+        let rows = vec![vec![1, 0, 1, 1]];
+        let mut line = Automaton::new(&rows, 2, Neighbourhood::line(1), Boundary::Growing, rule);
+
+        let repeat = line.run_until_repeat(10).unwrap();
+        assert_eq!(repeat, Repeat { start: 0, period: 1, shift: (0, 1) });
+
+        line.skip_to(1_000_000_000, &repeat);
+        assert_eq!(line.generation, 1_000_000_000);
+        assert_eq!(line.get(0, 1_000_000_000), 1);
+        assert_eq!(line.get(0, 1_000_000_001), 0);
+        assert_eq!(line.get(0, 1_000_000_003), 1);
+        assert_eq!(line.count(1), 3);
+
+        // Skipping a cycle doesn't move anything
+        let mut blinker = life(&[".....", "..#..", "..#..", "..#..", "....."], Boundary::Fixed);
+        let repeat = blinker.run_until_repeat(10).unwrap();
+        blinker.skip_to(1001, &repeat);
+        assert_eq!(blinker.get(2, 1), 1);
+        assert_eq!(blinker.get(1, 2), 0);
+    }
+}
@@ -0,0 +1,115 @@
+/// A fixed-length run of cells packed into 64-bit words.  Each cell takes a power-of-two number of
+/// bits, so a cell never straddles two words.
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct Cells {
+    bits: usize,
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl Cells {
+    /// Room for `len` cells that can each hold any of `states` states, all starting at state 0
+    pub fn new(states: usize, len: usize) -> Cells {
+        let bits = Cells::bits_for(states);
+        let words = (len * bits).div_ceil(64);
+
+        Cells {
+            bits,
+            len,
+            words: vec![0; words],
+        }
+    }
+
+    /// The number of bits each cell needs: 1, 2, 4 or 8
+    pub fn bits_for(states: usize) -> usize {
+        match states {
+            0..=2 => 1,
+            3..=4 => 2,
+            5..=16 => 4,
+            17..=256 => 8,
+            _ => panic!("Too many states: {}", states),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    pub fn get(&self, i: usize) -> u8 {
+        let bit = i * self.bits;
+        let mask = (1 << self.bits) - 1;
+        ((self.words[bit / 64] >> (bit % 64)) & mask) as u8
+    }
+
+    pub fn set(&mut self, i: usize, state: u8) {
+        let bit = i * self.bits;
+        let mask = (1 << self.bits) - 1;
+        let word = &mut self.words[bit / 64];
+        *word &= !(mask << (bit % 64));
+        *word |= (u64::from(state) & mask) << (bit % 64);
+    }
+
+    /// Number of cells in the given state
+    pub fn count(&self, state: u8) -> usize {
+        (0..self.len).filter(|i| self.get(*i) == state).count()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..self.len).map(move |i| self.get(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cells_bits_for() {
+        assert_eq!(Cells::bits_for(2), 1);
+        assert_eq!(Cells::bits_for(3), 2);
+        assert_eq!(Cells::bits_for(4), 2);
+        assert_eq!(Cells::bits_for(5), 4);
+        assert_eq!(Cells::bits_for(200), 8);
+    }
+
+    #[test]
+    fn test_cells_get_set() {
+        let mut cells = Cells::new(3, 100);
+        assert_eq!(cells.len(), 100);
+        assert_eq!(cells.words.len(), 4);
+        assert_eq!(cells.count(0), 100);
+
+        cells.set(0, 2);
+        cells.set(31, 1);
+        cells.set(32, 2);
+        cells.set(99, 1);
+        assert_eq!(cells.get(0), 2);
+        assert_eq!(cells.get(1), 0);
+        assert_eq!(cells.get(31), 1);
+        assert_eq!(cells.get(32), 2);
+        assert_eq!(cells.get(99), 1);
+        assert_eq!(cells.count(1), 2);
+
+        cells.set(0, 1);
+        assert_eq!(cells.get(0), 1);
+        assert_eq!(cells.iter().take(3).collect::<Vec<_>>(), vec![1, 0, 0]);
+    }
+
+    #[test]
+    fn test_cells_eq() {
+        let mut a = Cells::new(2, 10);
+        let mut b = Cells::new(2, 10);
+        a.set(3, 1);
+        assert_ne!(a, b);
+        b.set(3, 1);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,10 @@
+//! A small cellular automaton engine shared by day_12 (pots) and day_18 (lumber).  Cells are
+//! bit-packed, neighbourhoods are lists of offsets, and rules are closures.
+
+mod automaton;
+mod cells;
+mod neighbourhood;
+
+pub use crate::automaton::{Automaton, Boundary, Coordinate, CountRule, Repeat, Rule, State};
+pub use crate::cells::Cells;
+pub use crate::neighbourhood::Neighbourhood;
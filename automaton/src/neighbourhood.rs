@@ -0,0 +1,59 @@
+/// The cells a rule gets to look at, as (y, x) offsets from the cell being updated.  Rules see the
+/// neighbours' states in the same order as the offsets.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Neighbourhood {
+    pub offsets: Vec<(isize, isize)>,
+}
+
+impl Neighbourhood {
+    /// The eight surrounding cells, in reading order
+    pub fn moore() -> Neighbourhood {
+        let offsets = (-1..=1)
+            .flat_map(|y| (-1..=1).map(move |x| (y, x)))
+            .filter(|offset| *offset != (0, 0))
+            .collect();
+        Neighbourhood { offsets }
+    }
+
+    /// The four orthogonally adjacent cells, in reading order
+    pub fn von_neumann() -> Neighbourhood {
+        Neighbourhood { offsets: vec![(-1, 0), (0, -1), (0, 1), (1, 0)] }
+    }
+
+    /// A window of cells on the same row, from `radius` left to `radius` right, including the cell
+    /// itself
+    pub fn line(radius: usize) -> Neighbourhood {
+        let radius = radius as isize;
+        Neighbourhood { offsets: (-radius..=radius).map(|x| (0, x)).collect() }
+    }
+
+    /// The furthest any offset reaches in each axis, as (y, x)
+    pub fn reach(&self) -> (usize, usize) {
+        let reach_y = self.offsets.iter().map(|(y, _x)| y.unsigned_abs()).max().unwrap_or(0);
+        let reach_x = self.offsets.iter().map(|(_y, x)| x.unsigned_abs()).max().unwrap_or(0);
+        (reach_y, reach_x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighbourhood_moore() {
+        let moore = Neighbourhood::moore();
+        assert_eq!(moore.offsets.len(), 8);
+        assert_eq!(moore.offsets[0], (-1, -1));
+        assert_eq!(moore.offsets[3], (0, -1));
+        assert_eq!(moore.offsets[4], (0, 1));
+        assert_eq!(moore.reach(), (1, 1));
+    }
+
+    #[test]
+    fn test_neighbourhood_line() {
+        let line = Neighbourhood::line(2);
+        assert_eq!(line.offsets, vec![(0, -2), (0, -1), (0, 0), (0, 1), (0, 2)]);
+        assert_eq!(line.reach(), (0, 2));
+        assert_eq!(Neighbourhood::von_neumann().reach(), (1, 1));
+    }
+}
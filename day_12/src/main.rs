@@ -10,71 +10,98 @@ fn main() {
         .split("\n")
         .collect();
 
-    println!("part_1: {}", pot_sum(&input, 20));
-
-    // 50 billion generations turned out to be too much, even after making some optimizations to
-    // #add_or_remove_empty_start_end_pots.  After examining the state after thousands of
-    // generations, it became clear that the evolution becomes stable, and the sum of the pots can
-    // be described by: 52g + 1872 for large g.  To see for yourself, un-comment the code in
-    // #simulate.
-    let part_2: usize = 52 * 50_000_000_000 + 1872;
-    println!("part_2: {}", part_2);
+    let pot_sim = match parse_pot_sim(&input, false) {
+        Ok(pot_sim) => pot_sim,
+        Err(error) => {
+            println!("{}", error);
+            return;
+        },
+    };
+
+    println!("part_1: {}", pot_sum(&pot_sim, 20));
+
+    // 50 billion generations is too many to simulate one at a time, but the plants settle into a
+    // shape that slides along the row, so #simulate skips ahead once it spots it
+    println!("part_2: {}", pot_sum(&pot_sim, 50_000_000_000));
 }
 
 /// Returns sum of the numbers of all pots which contain a plant
-fn pot_sum(input: &Vec<&str>, generations: usize) -> isize {
-    let mut pot_sim = parse_pot_sim(input);
+fn pot_sum(pot_sim: &PotSim, generations: usize) -> isize {
+    let mut pot_sim = pot_sim.clone();
     simulate(&mut pot_sim, generations);
-
-    pot_sim.pots
-        .iter()
-        .map(|(pot_id, pot)| if pot.has_plant { *pot_id } else { 0 })
-        .sum()
+    pot_sim.pot_sum()
 }
 
-// What is the sum of the numbers of all pots which contain a plant
+// Advance the simulation to the given generation
 fn simulate(pot_sim: &mut PotSim, to_generation: usize) {
-    while pot_sim.generation < to_generation {
-        // if pot_sim.generation % 100_000 == 0 {
-        //     let sum: isize = pot_sim.pots
-        //         .iter()
-        //         .map(|(pot_id, pot)| if pot.has_plant { *pot_id } else { 0 })
-        //         .sum();
-        //     println!("Generation {} at with {} pots: {}", pot_sim.generation, pot_sim.pots.len(), sum);
-        // }
-        pot_sim.next_generation();
+    pot_sim.skip_to(to_generation);
+}
+
+/// Parses the initial state, a blank line and then the rules.  Patterns without a rule yield
+/// `default`.
+fn parse_pot_sim(input: &Vec<&str>, default: YieldsPlant) -> Result<PotSim, ParseError> {
+    let first_line = input.first().ok_or(ParseError::MissingInitialState)?;
+    let pots = first_line
+        .strip_prefix("initial state: ")
+        .and_then(parse_pots)
+        .ok_or_else(|| ParseError::MalformedInitialState { line: first_line.to_string() })?;
+
+    if let Some(line) = input.get(1).filter(|line| !line.is_empty()) {
+        return Err(ParseError::MissingBlankLine { line: line.to_string() });
     }
+
+    let rule_lines = input.get(2..).unwrap_or(&[]);
+    Ok(PotSim::new(&pots, parse_rules(rule_lines, default)?))
 }
 
-fn parse_pot_sim(input: &Vec<&str>) -> PotSim {
-    let pots = input[0][15..]
-        .chars()
-        .enumerate()
-        .map(|(pot_id, state)| {
-            let pot = Pot { has_plant: state == '#' };
-            (pot_id as isize, pot)
-        })
-        .collect();
+/// Parses lines like `...## => #`.  The first rule sets the width the rest must have.
+fn parse_rules(rule_lines: &[&str], default: YieldsPlant) -> Result<Rules, ParseError> {
+    let mut rules: Option<Rules> = None;
 
-    let rules = input[2..]
-        .iter()
-        .map(|rule_line| {
-            let r: Vec<bool> = rule_line[0..=4]
-                .chars()
-                .map(|state| state == '#')
-                .collect();
-            let rule = Rule(r[0], r[1], r[2], r[3], r[4]);
-            let yields_plant = rule_line[9..=9] == *"#";
-
-            (rule, yields_plant)
-        })
-        .collect();
+    for rule_line in rule_lines {
+        let malformed = || ParseError::MalformedRule { line: rule_line.to_string() };
+
+        let (pattern, result) = rule_line.split_once(" => ").ok_or_else(malformed)?;
+        let pattern = parse_pots(pattern).ok_or_else(malformed)?;
+        let yields_plant = match parse_pots(result).as_deref() {
+            Some([yields_plant]) => *yields_plant,
+            _ => return Err(malformed()),
+        };
 
-    PotSim {
-        generation: 0,
-        pots,
-        rules,
+        if pattern.len() % 2 == 0 {
+            return Err(ParseError::EvenWidth { line: rule_line.to_string() });
+        }
+        if pattern.len() > MAX_RULE_WIDTH {
+            return Err(ParseError::TooWide { line: rule_line.to_string() });
+        }
+
+        let rules = rules.get_or_insert_with(|| Rules::new(pattern.len(), default));
+        if pattern.len() != rules.width {
+            return Err(ParseError::MismatchedWidth {
+                line: rule_line.to_string(),
+                expected: rules.width,
+            });
+        }
+        rules.patterns.insert(Rule(pattern), yields_plant);
+    }
+
+    // With no rules at all there's nothing to say how wide a window is, so just look at the pot
+    let rules = rules.unwrap_or_else(|| Rules::new(1, default));
+    if rules.yields_plant(&Rule(vec![false; rules.width])) {
+        return Err(ParseError::EmptyYieldsPlant);
     }
+    Ok(rules)
+}
+
+/// Parses pots like `#..#`, or None if there's anything but pots
+fn parse_pots(pots: &str) -> Option<Vec<bool>> {
+    pots.chars()
+        .map(|pot| match pot {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -104,38 +131,139 @@ mod tests {
 
     #[test]
     fn test_parse_pot_sim() {
-        let pot_sim = parse_pot_sim(&test_input());
+        let pot_sim = parse_pot_sim(&test_input(), false).unwrap();
+
+        assert_eq!(pot_sim.generation(), 0);
+
+        assert_eq!(pot_sim.automaton.width(), 25);
+        assert_eq!(pot_sim.has_plant(0), true);
+        assert_eq!(pot_sim.has_plant(1), false);
+        assert_eq!(pot_sim.has_plant(2), false);
+        assert_eq!(pot_sim.has_plant(3), true);
+
+        let rules = parse_rules(&test_input()[2..], false).unwrap();
+        assert_eq!(rules.patterns.len(), 14);
+        assert_eq!(rules.width, 5);
+        assert!(!rules.yields_plant(&Rule(vec![true; 5])));
+        assert!(rules.yields_plant(&Rule(vec![false, false, false, true, true])));
+        assert!(rules.yields_plant(&Rule(vec![false, false, true, false, false])));
+        assert_eq!(Rule(vec![false, false, true, false, true]).packed(), 0b00101);
+    }
+
+    #[test]
+    fn test_parse_rules_width() {
+        // Rule 90: each pot gets a plant if exactly one of its neighbours had one
+        let input = vec![
+            "initial state: #",
+            "",
+            "#.# => .",
+            "#.. => #",
+            "..# => #",
+            "##. => #",
+            ".## => #",
+        ];
+        let mut pot_sim = parse_pot_sim(&input, false).unwrap();
+        simulate(&mut pot_sim, 2);
+        assert_eq!(format!("{:?}", pot_sim).lines().count(), 5);
+        assert_eq!(pot_sim.pot_sum(), -2 + 2);
+        assert!(!pot_sim.has_plant(0));
 
-        assert_eq!(pot_sim.generation, 0);
+        // Everything not listed yields a plant instead
+        let rules = parse_rules(&["... => .", "#.# => ."], true).unwrap();
+        assert!(rules.yields_plant(&Rule(vec![true, true, true])));
+        assert!(!rules.yields_plant(&Rule(vec![true, false, true])));
+    }
 
-        assert_eq!(pot_sim.pots.len(), 25);
-        assert_eq!(pot_sim.pots.get(&0).unwrap().has_plant, true);
-        assert_eq!(pot_sim.pots.get(&1).unwrap().has_plant, false);
-        assert_eq!(pot_sim.pots.get(&2).unwrap().has_plant, false);
-        assert_eq!(pot_sim.pots.get(&3).unwrap().has_plant, true);
+    #[test]
+    fn test_parse_errors() {
+        let error = |input: Vec<&str>| parse_pot_sim(&input, false).err().unwrap();
+        let line = |line: &str| line.to_string();
 
-        assert_eq!(pot_sim.rules.len(), 14);
-        assert_eq!(*pot_sim.rules.get(&Rule(false, false, false, true, true)).unwrap(), true);
-        assert_eq!(*pot_sim.rules.get(&Rule(false, false, true, false, false)).unwrap(), true);
+        assert_eq!(error(vec![]), ParseError::MissingInitialState);
+        assert_eq!(
+            error(vec!["initial: #.#"]),
+            ParseError::MalformedInitialState { line: line("initial: #.#") },
+        );
+        assert_eq!(
+            error(vec!["initial state: #", "#.# => #", "..# => #"]),
+            ParseError::MissingBlankLine { line: line("#.# => #") },
+        );
+        assert_eq!(
+            error(vec!["initial state: #", "", "#.# =>"]),
+            ParseError::MalformedRule { line: line("#.# =>") },
+        );
+        assert_eq!(
+            error(vec!["initial state: #", "", "#x# => #"]),
+            ParseError::MalformedRule { line: line("#x# => #") },
+        );
+        assert_eq!(
+            error(vec!["initial state: #", "", "#.# => ##"]),
+            ParseError::MalformedRule { line: line("#.# => ##") },
+        );
+        assert_eq!(
+            error(vec!["initial state: #", "", "#. => #"]),
+            ParseError::EvenWidth { line: line("#. => #") },
+        );
+        let too_wide = format!("{} => #", ".".repeat(MAX_RULE_WIDTH + 1));
+        assert_eq!(
+            error(vec!["initial state: #", "", &too_wide]),
+            ParseError::TooWide { line: too_wide.clone() },
+        );
+        assert_eq!(
+            error(vec!["initial state: #", "", "#.# => #", "..#.. => #"]),
+            ParseError::MismatchedWidth { line: line("..#.. => #"), expected: 3 },
+        );
+        assert_eq!(
+            parse_pot_sim(&vec!["initial state: #", "", "#.# => #"], true).err(),
+            Some(ParseError::EmptyYieldsPlant),
+        );
+        assert!(parse_pot_sim(&vec!["initial state: #", "", "... => ."], true).is_ok());
     }
 
     #[test]
     fn test_simulate() {
-        let mut pot_sim_3 = parse_pot_sim(&test_input());
+        let mut pot_sim_3 = parse_pot_sim(&test_input(), false).unwrap();
         simulate(&mut pot_sim_3, 3);
-        assert_eq!(pot_sim_3.pots.get(&-1).unwrap().has_plant, true);
-        assert_eq!(pot_sim_3.pots.get(&0).unwrap().has_plant, false);
-        assert_eq!(pot_sim_3.pots.get(&1).unwrap().has_plant, true);
+        assert_eq!(pot_sim_3.has_plant(-1), true);
+        assert_eq!(pot_sim_3.has_plant(0), false);
+        assert_eq!(pot_sim_3.has_plant(1), true);
 
-        let mut pot_sim_20 = parse_pot_sim(&test_input());
+        let mut pot_sim_20 = parse_pot_sim(&test_input(), false).unwrap();
         simulate(&mut pot_sim_20, 20);
-        assert_eq!(pot_sim_20.pots.get(&-1).unwrap().has_plant, false);
-        assert_eq!(pot_sim_20.pots.get(&0).unwrap().has_plant, false);
-        assert_eq!(pot_sim_20.pots.get(&1).unwrap().has_plant, false);
+        assert_eq!(pot_sim_20.has_plant(-1), false);
+        assert_eq!(pot_sim_20.has_plant(0), false);
+        assert_eq!(pot_sim_20.has_plant(1), false);
     }
 
     #[test]
     fn test_part_1() {
-        assert_eq!(pot_sum(&test_input(), 20), 325);
+        assert_eq!(pot_sum(&parse_pot_sim(&test_input(), false).unwrap(), 20), 325);
+    }
+
+    #[test]
+    fn test_simulate_glider() {
+        // Stepping one generation at a time agrees with skipping ahead
+        let mut stepped = parse_pot_sim(&test_input(), false).unwrap();
+        while stepped.generation() < 1000 {
+            stepped.next_generation();
+        }
+        let mut skipped = parse_pot_sim(&test_input(), false).unwrap();
+        simulate(&mut skipped, 1000);
+        assert_eq!(skipped.generation(), 1000);
+        assert_eq!(skipped.pot_sum(), stepped.pot_sum());
+
+        // The example settles into a glider that moves one pot per generation
+        let glider = skipped.glider.unwrap();
+        assert_eq!((glider.shift, glider.period), ((0, 1), 1));
+        let sum_1000 = skipped.pot_sum();
+        simulate(&mut skipped, 1_000_000_000_000);
+        let plants = stepped.automaton.count(1) as isize;
+        assert_eq!(skipped.pot_sum(), sum_1000 + plants * (1_000_000_000_000 - 1000));
+
+        // Not enough generations to spot the glider
+        let mut short = parse_pot_sim(&test_input(), false).unwrap();
+        simulate(&mut short, 20);
+        assert_eq!(short.glider, None);
+        assert_eq!(short.pot_sum(), 325);
     }
 }
@@ -1,106 +1,193 @@
-use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
+use std::rc::Rc;
+
+use automaton::{Automaton, Boundary, Neighbourhood, Repeat};
 
 pub type PotId = isize;
 
-pub struct Pot {
-    pub has_plant: bool,
+/// A window of pots centred on the pot being updated, e.g. LLCRR, with true for a plant
+#[derive(Eq, PartialEq, Hash, Clone, Debug)]
+pub struct Rule(pub Vec<bool>);
+pub type YieldsPlant = bool;
+
+/// Rules are packed into a usize to look them up, one bit per pot
+pub const MAX_RULE_WIDTH: usize = usize::BITS as usize;
+
+impl Rule {
+    /// The pots as bits, leftmost pot highest, e.g. ..#.# is 0b00101
+    pub fn packed(&self) -> usize {
+        pack(self.0.iter().map(|has_plant| *has_plant as u8))
+    }
 }
 
-pub type Pots = BTreeMap<PotId, Pot>;
+fn pack(pots: impl Iterator<Item = u8>) -> usize {
+    pots.fold(0, |bits, pot| bits << 1 | pot as usize)
+}
 
-// LLCRR
-#[derive(Eq, PartialEq, Hash, Debug)]
-pub struct Rule(pub bool, pub bool, pub bool, pub bool, pub bool);
-pub type YieldsPlant = bool;
-pub type Rules = HashMap<Rule, YieldsPlant>;
+/// Every rule from the input, which all share an odd width
+#[derive(Clone, Debug)]
+pub struct Rules {
+    pub width: usize,
+    pub patterns: HashMap<Rule, YieldsPlant>,
+    /// What patterns without a rule yield
+    pub default: YieldsPlant,
+}
+
+impl Rules {
+    pub fn new(width: usize, default: YieldsPlant) -> Rules {
+        Rules {
+            width,
+            patterns: HashMap::new(),
+            default,
+        }
+    }
+
+    pub fn yields_plant(&self, rule: &Rule) -> YieldsPlant {
+        *self.patterns.get(rule).unwrap_or(&self.default)
+    }
+}
+
+/// A problem with the puzzle input found by #parse_pot_sim
+#[derive(Eq, PartialEq, Debug)]
+pub enum ParseError {
+    MissingInitialState,
+    MalformedInitialState {
+        line: String,
+    },
+    MissingBlankLine {
+        line: String,
+    },
+    MalformedRule {
+        line: String,
+    },
+    EvenWidth {
+        line: String,
+    },
+    TooWide {
+        line: String,
+    },
+    MismatchedWidth {
+        line: String,
+        expected: usize,
+    },
+    /// Pots with no plants in reach would sprout plants, so the row would fill up forever
+    EmptyYieldsPlant,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingInitialState => write!(f, "no initial state"),
+            ParseError::MalformedInitialState { line } => {
+                write!(f, "'{}': expected 'initial state: ' then pots", line)
+            },
+            ParseError::MissingBlankLine { line } => {
+                write!(f, "'{}': expected a blank line after the initial state", line)
+            },
+            ParseError::MalformedRule { line } => {
+                write!(f, "'{}': expected pots, ' => ' and a pot", line)
+            },
+            ParseError::EvenWidth { line } => {
+                write!(f, "'{}': rule has no centre pot", line)
+            },
+            ParseError::TooWide { line } => {
+                write!(f, "'{}': rules can be at most {} pots wide", line, MAX_RULE_WIDTH)
+            },
+            ParseError::MismatchedWidth { line, expected } => {
+                write!(f, "'{}': expected a rule {} pots wide", line, expected)
+            },
+            ParseError::EmptyYieldsPlant => {
+                write!(f, "a pot with no plants nearby can't yield a plant")
+            },
+        }
+    }
+}
 
+/// A row of pots, numbered from 0 at the first pot of the initial state.  Each pot is a cell in a
+/// one-dimensional automaton (1 = plant) that grows in either direction as plants spread.
+#[derive(Clone)]
 pub struct PotSim {
-    pub generation: usize,
-    pub pots: Pots,
-    pub rules: Rules,
+    pub automaton: Automaton,
+    /// Set once the plants have settled into a shape that slides along the row
+    pub glider: Option<Repeat>,
 }
 
 impl PotSim {
-    pub fn next_generation(&mut self) {
-        self.add_or_remove_empty_start_end_pots(5);
-
-        let min_pot_id = self.pots.iter().nth(0).unwrap().0.to_owned();
-        let max_pot_id = self.pots.iter().last().unwrap().0.to_owned();
-        let mut next_has_plants = HashMap::new();
-
-        // Populate the "next_has_plant" field for each Pot
-        for (pot_id, _pot) in &self.pots {
-            if *pot_id < min_pot_id + 2 { continue; }
-            if *pot_id > max_pot_id - 2 { continue; }
-
-            let rule = Rule(
-                self.pots.get(&(pot_id - 2)).unwrap().has_plant,
-                self.pots.get(&(pot_id - 1)).unwrap().has_plant,
-                self.pots.get(&(pot_id + 0)).unwrap().has_plant,
-                self.pots.get(&(pot_id + 1)).unwrap().has_plant,
-                self.pots.get(&(pot_id + 2)).unwrap().has_plant,
-            );
-
-            // Look up the Rule and save whether this pot will have a plant next generation
-            let yields_plant = match self.rules.get(&rule) {
-                Some(yp) => *yp,
-                None => false,
-            };
-            next_has_plants.insert(pot_id.to_owned(), yields_plant);
-        }
+    pub fn new(pots: &[bool], rules: Rules) -> PotSim {
+        let row = pots.iter().map(|has_plant| *has_plant as u8).collect();
+
+        let radius = rules.width / 2;
 
-        // Persist the "next" generation as the new current generation and reset the "next"
-        for (pot_id, next_has_plant) in next_has_plants {
-            self.pots.get_mut(&pot_id).unwrap().has_plant = next_has_plant;
+        // Look each pot's window, e.g. LLCRR, up by its packed bits, rather than building a Rule
+        // for every pot every generation
+        let patterns = rules
+            .patterns
+            .iter()
+            .map(|(rule, yields_plant)| (rule.packed(), *yields_plant))
+            .collect::<HashMap<_, _>>();
+        let default = rules.default;
+        let rule = Rc::new(move |_pot, window: &[u8]| {
+            let packed = pack(window.iter().copied());
+            *patterns.get(&packed).unwrap_or(&default) as u8
+        });
+
+        let automaton = Automaton::new(
+            &[row],
+            2,
+            Neighbourhood::line(radius),
+            Boundary::Growing,
+            rule,
+        );
+
+        PotSim {
+            automaton,
+            glider: None,
         }
+    }
 
-        self.generation += 1;
+    pub fn generation(&self) -> usize {
+        self.automaton.generation
     }
 
-    // Add or remove empty start and end pots to keep a 5-pot buffer.  Added after examining
-    // later generations for Part 2 - it's a stable "glider" configuration, that goes in the
-    // positive direction, so trimming pots from the start is useful.
-    pub fn add_or_remove_empty_start_end_pots(&mut self, buffer: usize) {
-        let ibuffer = buffer as isize;
-
-        let empty_at_start = self.pots.iter().take_while(|(_, pot)| !pot.has_plant).count();
-        let empty_at_end = self.pots.iter().rev().take_while(|(_, pot)| !pot.has_plant).count();
-        let min_plant_id = *self.pots.iter().find(|(_, pot)| pot.has_plant).unwrap().0;
-        let max_plant_id = *self.pots.iter().rev().find(|(_, pot)| pot.has_plant).unwrap().0;
-
-        // Add or remove at start
-        if empty_at_start < buffer {
-            for pot_id in (min_plant_id - ibuffer)..min_plant_id {
-                self.pots.insert(pot_id, Pot { has_plant: false });
-            }
-        } else if empty_at_start > buffer {
-            let min_id = *self.pots.iter().nth(0).unwrap().0;
-            for pot_id in min_id..(min_plant_id - ibuffer) {
-                self.pots.remove(&pot_id);
-            }
+    pub fn has_plant(&self, pot_id: PotId) -> bool {
+        self.automaton.get(0, pot_id) == 1
+    }
+
+    pub fn next_generation(&mut self) {
+        self.automaton.step();
+    }
+
+    /// Advances to the given generation.  Watches for the plants settling into a glider on the
+    /// way, and once they have, skips whole periods at a time by just moving the glider along.
+    pub fn skip_to(&mut self, generation: usize) {
+        if self.glider.is_none() && generation > self.generation() {
+            self.glider = self.automaton.run_until_repeat(generation - self.generation());
         }
 
-        // Add or remove at end
-        if empty_at_end < buffer {
-            for pot_id in (max_plant_id + 1)..=(max_plant_id + ibuffer) {
-                self.pots.insert(pot_id, Pot { has_plant: false });
-            }
-        } else if empty_at_end > buffer {
-            for pot_id in (max_plant_id - (empty_at_end as isize) + ibuffer + 1)..=max_plant_id {
-                self.pots.remove(&pot_id);
-            }
+        match self.glider {
+            Some(glider) => self.automaton.skip_to(generation, &glider),
+            None => while self.generation() < generation {
+                self.next_generation();
+            },
         }
     }
+
+    /// Sum of the numbers of all pots which contain a plant
+    pub fn pot_sum(&self) -> isize {
+        self.automaton
+            .iter()
+            .map(|((_y, pot_id), plant)| if plant == 1 { pot_id } else { 0 })
+            .sum()
+    }
 }
 
 impl fmt::Debug for PotSim {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let plants = self.pots
+        let plants = self.automaton
             .iter()
-            .map(|(pot_id, pot)| {
-                let plant = if pot.has_plant { '#' } else { '.' };
+            .map(|((_y, pot_id), _plant)| {
+                let plant = if self.has_plant(pot_id) { '#' } else { '.' };
                 format!("{: >5} {}", pot_id, plant)
             })
             .collect::<Vec<String>>()
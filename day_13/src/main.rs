@@ -2,7 +2,11 @@
 
 mod types;
 
-use crate::types::System;
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+
+use crate::types::{CartId, CollisionPolicy, System};
 
 fn main() {
     let input = include_str!("input.txt")
@@ -10,34 +14,132 @@ fn main() {
         .split("\n")
         .collect();
 
+    let errors = System::validate(&input);
+    if !errors.is_empty() {
+        for error in errors {
+            println!("{}", error);
+        }
+        return;
+    }
+
+    // `cargo run -- crashes <remove|stop|bounce> <ticks>` prints every crash under that policy
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("crashes") {
+        let policy = match args.get(1).map(String::as_str) {
+            Some("stop") => CollisionPolicy::StopBoth,
+            Some("bounce") => CollisionPolicy::Bounce,
+            _ => CollisionPolicy::RemoveBoth,
+        };
+        let ticks = args.get(2).and_then(|t| t.parse().ok()).unwrap_or(1000);
+        for line in crash_log(&input, policy, ticks) {
+            println!("{}", line);
+        }
+        return;
+    }
+
+    // `cargo run -- predict <cart> <tick>` prints where a cart will be at that tick
+    if args.first().map(String::as_str) == Some("predict") {
+        let id = args.get(1).and_then(|id| id.parse().ok()).unwrap_or(0);
+        let tick = args.get(2).and_then(|t| t.parse().ok()).unwrap_or(0);
+        match predict(&input, id, tick) {
+            Some(coord) => println!("cart {} at tick {}: {}", id, tick, coord),
+            None => println!("cart {} is gone by tick {}", id, tick),
+        }
+        return;
+    }
+
     println!("part_1: {}", first_crash(&input));
     println!("part_2: {}", last_cart(&input));
 }
 
+/// How the carts end up once crashing carts are removed
+#[derive(Eq, PartialEq, Debug)]
+enum LastCart {
+    At(String),
+    // An even number of carts crashed into each other, leaving none
+    NoneLeft,
+    // The carts go round in circles without ever being whittled down to one
+    Circling,
+}
+
+impl fmt::Display for LastCart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LastCart::At(coord) => write!(f, "{}", coord),
+            LastCart::NoneLeft => write!(f, "no carts left"),
+            LastCart::Circling => write!(f, "carts circle forever without crashing down to one"),
+        }
+    }
+}
+
 // Find the location of the first crash
 fn first_crash(lines: &Vec<&str>) -> String {
     let mut system = System::parse(lines);
 
-    loop {
-        let crashed = system.next_tick();
-        if !crashed.is_empty() {
-            let coord = crashed.iter().nth(0).unwrap();
-            return format!("{},{}", coord.x, coord.y);
-        }
+    while system.crashes.is_empty() {
+        system.next_tick();
     }
+
+    let coord = system.crashes[0].coord;
+    format!("{},{}", coord.x, coord.y)
 }
 
 // Find the location of the last cart (crashing carts are removed instantly)
-fn last_cart(lines: &Vec<&str>) -> String {
+fn last_cart(lines: &Vec<&str>) -> LastCart {
     let mut system = System::parse(lines);
+    let mut seen = HashSet::new();
 
-    loop {
-        system.next_tick();
-        if system.carts.len() == 1 {
-            let last_cart_coord = system.carts.keys().nth(0).unwrap();
-            return format!("{},{}", last_cart_coord.x, last_cart_coord.y);
+    while system.carts.len() > 1 {
+        if !seen.insert(system.carts.clone()) {
+            return LastCart::Circling;
         }
+        system.next_tick();
+    }
+
+    match system.carts.keys().next() {
+        Some(coord) => LastCart::At(format!("{},{}", coord.x, coord.y)),
+        None => LastCart::NoneLeft,
+    }
+}
+
+// Where the cart is at the given tick, found by simulating until the carts repeat a state
+fn predict(lines: &Vec<&str>, id: CartId, tick: usize) -> Option<String> {
+    let mut system = System::parse(lines);
+    let forecast = system.find_cycle(1_000_000)?;
+    let coord = forecast.cart_coord_at(id, tick)?;
+    Some(format!("{},{}", coord.x, coord.y))
+}
+
+// Describe every crash in the first "ticks" ticks, then where each cart ended up
+fn crash_log(lines: &Vec<&str>, policy: CollisionPolicy, ticks: usize) -> Vec<String> {
+    let mut system = System::parse(lines);
+    system.collision_policy = policy;
+    let cart_count = system.carts.len();
+
+    while system.tick < ticks {
+        system.next_tick();
+    }
+
+    let mut log = system.crashes
+        .iter()
+        .map(|crash| format!(
+            "tick {}: cart {} ran into cart {} at {},{}",
+            crash.tick, crash.cart_ids.0, crash.cart_ids.1, crash.coord.x, crash.coord.y,
+        ))
+        .collect::<Vec<_>>();
+
+    for id in 0..cart_count {
+        let crash_count = system.crashes_of(id).len();
+        let line = match system.cart_coord(id) {
+            Some(coord) => {
+                format!("cart {}: at {},{} after {} crashes", id, coord.x, coord.y, crash_count)
+            },
+            None => format!("cart {}: removed after {} crashes", id, crash_count),
+        };
+        log.push(line);
     }
+
+    log
 }
 
 #[cfg(test)]
@@ -91,6 +193,40 @@ mod tests {
 
     #[test]
     fn test_last_cart() {
-        assert_eq!(last_cart(&test_input_2()), "6,4".to_string());
+        assert_eq!(last_cart(&test_input_2()), LastCart::At("6,4".to_string()));
+
+        // Two carts chasing each other around a loop never meet
+        assert_eq!(last_cart(&vec![
+            "/>-\\",
+            "|  |",
+            "\\-</",
+        ]), LastCart::Circling);
+
+        // Two carts heading straight for each other take each other out
+        assert_eq!(last_cart(&vec![
+            "/>-<\\",
+            "|   |",
+            "\\---/",
+        ]), LastCart::NoneLeft);
+    }
+
+    #[test]
+    fn test_predict() {
+        assert_eq!(predict(&test_input_2(), 4, 1_000_000_000_000), None);
+        assert_eq!(predict(&test_input_2(), 8, 3), Some("6,4".to_string()));
+    }
+
+    #[test]
+    fn test_crash_log() {
+        assert_eq!(crash_log(&test_input_1(), CollisionPolicy::RemoveBoth, 20), vec![
+            "tick 14: cart 0 ran into cart 1 at 7,3",
+            "cart 0: removed after 1 crashes",
+            "cart 1: removed after 1 crashes",
+        ]);
+        assert_eq!(crash_log(&test_input_1(), CollisionPolicy::StopBoth, 20), vec![
+            "tick 14: cart 0 ran into cart 1 at 7,3",
+            "cart 0: at 7,4 after 1 crashes",
+            "cart 1: at 7,3 after 1 crashes",
+        ]);
     }
 }
@@ -1,11 +1,15 @@
 use std::collections::HashMap;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashSet;
+use std::fmt;
 
 pub struct System {
     pub nodes: Nodes,
     pub carts: Carts,
     pub tick: usize,
+    pub collision_policy: CollisionPolicy,
+    pub crashes: Vec<Crash>,
 }
 
 pub type Nodes = HashMap<NodeCoordinate, Node>;
@@ -51,20 +55,44 @@ pub enum Node {
     },
 }
 
-#[derive(Eq, PartialEq, Debug, Clone)]
+#[derive(Eq, PartialEq, Hash, Debug, Clone)]
 pub struct Cart {
+    pub id: CartId,
     pub direction: Direction,
     pub next_turn: TurnDirection,
+    pub is_stopped: bool,
+}
+
+/// Carts are numbered in reading order when the system is parsed, and keep their id as they move
+pub type CartId = usize;
+
+/// What happens to two carts when one runs into the other
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum CollisionPolicy {
+    /// Both carts are removed from the tracks instantly
+    RemoveBoth,
+    /// Both carts stay where they are for good, and become an obstacle for other carts
+    StopBoth,
+    /// The moving cart stays where it is, and both carts turn around
+    Bounce,
 }
 
 #[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Crash {
+    pub tick: usize,
+    pub coord: NodeCoordinate,
+    /// The cart that moved, followed by the cart it ran into
+    pub cart_ids: (CartId, CartId),
+}
+
+#[derive(Eq, PartialEq, Hash, Debug, Clone)]
 pub enum TurnDirection {
     Left,
     Right,
     Straight,
 }
 
-#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Copy, Clone)]
 pub enum Direction {
     Up,
     Right,
@@ -72,10 +100,62 @@ pub enum Direction {
     Left,
 }
 
+/// The cart states seen while looking for a cycle, from the tick the search started until the
+/// first repeated state
+pub struct Forecast {
+    pub first_tick: usize,
+    pub states: Vec<Carts>,
+    /// The first tick of the repeating part
+    pub start: usize,
+    /// Number of ticks before the state repeats
+    pub period: usize,
+}
+
+impl Forecast {
+    /// The carts at any tick from first_tick on, without simulating every tick in between
+    pub fn carts_at(&self, tick: usize) -> Option<&Carts> {
+        if tick < self.first_tick {
+            None
+        } else if tick < self.start {
+            self.states.get(tick - self.first_tick)
+        } else {
+            self.states.get(self.start - self.first_tick + (tick - self.start) % self.period)
+        }
+    }
+
+    pub fn cart_coord_at(&self, id: CartId, tick: usize) -> Option<NodeCoordinate> {
+        self.carts_at(tick)?
+            .iter()
+            .find(|(_coord, cart)| cart.id == id)
+            .map(|(coord, _cart)| *coord)
+    }
+}
+
+impl Direction {
+    pub fn reverse(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+}
+
 impl System {
     pub fn parse(lines: &Vec<&str>) -> System {
         let mut nodes: Nodes = HashMap::new();
         let mut carts: Carts = BTreeMap::new();
+        let mut cart_id = 0;
+        let mut new_cart = |direction| {
+            cart_id += 1;
+            Cart {
+                id: cart_id - 1,
+                direction,
+                next_turn: TurnDirection::Left,
+                is_stopped: false,
+            }
+        };
 
         for (y, line) in lines.iter().enumerate() {
             let mut chars_iter = line.chars().enumerate().peekable();
@@ -153,40 +233,28 @@ impl System {
                             coord,
                             Node::StraightUd { up: up(), down: down() },
                         );
-                        carts.insert(
-                            coord,
-                            Cart { direction: Direction::Up, next_turn: TurnDirection::Left },
-                        );
+                        carts.insert(coord, new_cart(Direction::Up));
                     },
                     'v' => {
                         nodes.insert(
                             coord,
                             Node::StraightUd { up: up(), down: down() },
                         );
-                        carts.insert(
-                            coord,
-                            Cart { direction: Direction::Down, next_turn: TurnDirection::Left },
-                        );
+                        carts.insert(coord, new_cart(Direction::Down));
                     },
                     '>' => {
                         nodes.insert(
                             coord,
                             Node::StraightLr { right: right(), left: left() },
                         );
-                        carts.insert(
-                            coord,
-                            Cart { direction: Direction::Right, next_turn: TurnDirection::Left },
-                        );
+                        carts.insert(coord, new_cart(Direction::Right));
                     },
                     '<' => {
                         nodes.insert(
                             coord,
                             Node::StraightLr { right: right(), left: left() },
                         );
-                        carts.insert(
-                            coord,
-                            Cart { direction: Direction::Left, next_turn: TurnDirection::Left },
-                        );
+                        carts.insert(coord, new_cart(Direction::Left));
                     },
                     ' ' => {},
                     _ => panic!("Unmatched character!"),
@@ -198,9 +266,27 @@ impl System {
             nodes,
             carts,
             tick: 0,
+            collision_policy: CollisionPolicy::RemoveBoth,
+            crashes: Vec::new(),
         }
     }
 
+    /// Where the cart with this id currently is, if it's still on the tracks
+    pub fn cart_coord(&self, id: CartId) -> Option<NodeCoordinate> {
+        self.carts
+            .iter()
+            .find(|(_coord, cart)| cart.id == id)
+            .map(|(coord, _cart)| *coord)
+    }
+
+    /// Every crash the cart with this id has been involved in, in the order they happened
+    pub fn crashes_of(&self, id: CartId) -> Vec<&Crash> {
+        self.crashes
+            .iter()
+            .filter(|crash| crash.cart_ids.0 == id || crash.cart_ids.1 == id)
+            .collect()
+    }
+
     pub fn next_coord(coord: &NodeCoordinate, direction: &Direction) -> NodeCoordinate {
         let mut y = coord.y;
         let mut x = coord.x;
@@ -214,11 +300,28 @@ impl System {
     }
 
     pub fn next_tick(&mut self) -> HashSet<NodeCoordinate> {
-        let mut new_carts = self.carts.clone();
         let mut crashed_coords = HashSet::new();
 
-        for (coord, cart) in &self.carts {
-            let next_coord = Self::next_coord(coord, &cart.direction);
+        // Carts move in the order they were in at the start of the tick.  Keep track of where each
+        // one is by id, since earlier carts may have run into it in the meantime.
+        let cart_ids = self.carts.values().map(|cart| cart.id).collect::<Vec<_>>();
+        let mut cart_coords = self
+            .carts
+            .iter()
+            .map(|(coord, cart)| (cart.id, *coord))
+            .collect::<HashMap<CartId, NodeCoordinate>>();
+
+        for id in cart_ids {
+            let coord = match cart_coords.get(&id) {
+                Some(coord) => *coord,
+                None => continue,
+            };
+            let cart = self.carts[&coord].clone();
+            if cart.is_stopped {
+                continue;
+            }
+
+            let next_coord = Self::next_coord(&coord, &cart.direction);
             let next_node = self.nodes.get(&next_coord).unwrap();
 
             let (new_direction, new_next_turn) = match next_node {
@@ -283,32 +386,271 @@ impl System {
                 },
             };
 
-            if new_carts.get(&next_coord).is_some() {
+            if let Some(other) = self.carts.get(&next_coord) {
                 // A cart already exists in this cart's next position
-                //   - Remove both carts
-                //   - Save the crash coordinate to "crash_coords"
-                new_carts.remove(&coord).unwrap();
-                new_carts.remove(&next_coord).unwrap();
+                self.crashes.push(Crash {
+                    tick: self.tick + 1,
+                    coord: next_coord,
+                    cart_ids: (id, other.id),
+                });
                 crashed_coords.insert(next_coord);
+
+                match self.collision_policy {
+                    CollisionPolicy::RemoveBoth => {
+                        let other_id = other.id;
+                        self.carts.remove(&coord).unwrap();
+                        self.carts.remove(&next_coord).unwrap();
+                        cart_coords.remove(&id);
+                        cart_coords.remove(&other_id);
+                    },
+                    CollisionPolicy::StopBoth => {
+                        self.carts.get_mut(&coord).unwrap().is_stopped = true;
+                        self.carts.get_mut(&next_coord).unwrap().is_stopped = true;
+                    },
+                    CollisionPolicy::Bounce => {
+                        for c in [coord, next_coord].iter() {
+                            let cart = self.carts.get_mut(c).unwrap();
+                            cart.direction = cart.direction.reverse();
+                        }
+                    },
+                }
             } else {
-                // The cart at new_carts[coord] may already have been removed during a collision,
-                // so only insert if there was an existing cart to remove
-                new_carts.remove(&coord).and_then(|_| {
-                    new_carts.insert(
-                        next_coord,
-                        Cart {
-                            direction: new_direction,
-                            next_turn: new_next_turn,
-                        },
-                    )
-                });
+                self.carts.remove(&coord).unwrap();
+                cart_coords.insert(id, next_coord);
+                self.carts.insert(
+                    next_coord,
+                    Cart {
+                        id,
+                        direction: new_direction,
+                        next_turn: new_next_turn,
+                        is_stopped: false,
+                    },
+                );
             }
         }
 
-        self.carts = new_carts;
         self.tick += 1;
         crashed_coords
     }
+
+    /// Runs the system until the carts (positions, directions and next turns) are in a state they
+    /// have been in before.  Returns None if that doesn't happen within max_ticks.
+    pub fn find_cycle(&mut self, max_ticks: usize) -> Option<Forecast> {
+        let first_tick = self.tick;
+        let mut seen: HashMap<Carts, usize> = HashMap::new();
+        let mut states = Vec::new();
+
+        while self.tick - first_tick <= max_ticks {
+            if let Some(start) = seen.get(&self.carts) {
+                return Some(Forecast {
+                    first_tick,
+                    states,
+                    start: *start,
+                    period: self.tick - start,
+                });
+            }
+
+            seen.insert(self.carts.clone(), self.tick);
+            states.push(self.carts.clone());
+            self.next_tick();
+        }
+
+        None
+    }
+
+    /// Checks ASCII tracks for problems that System#parse would silently accept (or panic on):
+    /// track ends that don't connect to anything, carts drawn on track that doesn't match their
+    /// direction, and curves that could be read either way
+    pub fn validate(lines: &Vec<&str>) -> Vec<TrackError> {
+        let tiles = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_x, c)| *c != ' ')
+                    .map(move |(x, c)| (NodeCoordinate { y, x }, c))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut errors = BTreeSet::new();
+
+        // The directions each tile connects to, read the same way System#parse reads them
+        let mut connections = BTreeMap::new();
+        for (coord, c) in tiles.iter() {
+            let next = tiles.get(&NodeCoordinate { y: coord.y, x: coord.x + 1 }).cloned();
+            match tile_directions(*c, next) {
+                Some(directions) => { connections.insert(*coord, directions); },
+                None => { errors.insert(TrackError::UnknownTile { coord: *coord, tile: *c }); },
+            }
+        }
+
+        for (coord, directions) in connections.iter() {
+            let c = tiles[coord];
+
+            // Curves are ambiguous if track leads into both of their possible orientations, or
+            // into neither
+            if c == '/' || c == '\\' {
+                let leads_in = |direction: Direction| {
+                    neighbor(coord, direction)
+                        .and_then(|n| connections.get(&n))
+                        .is_some_and(|ds| ds.contains(&direction.reverse()))
+                };
+                let (a, b) = if c == '/' {
+                    ([Direction::Down, Direction::Right], [Direction::Up, Direction::Left])
+                } else {
+                    ([Direction::Up, Direction::Right], [Direction::Down, Direction::Left])
+                };
+                let a_leads_in = a.iter().any(|d| leads_in(*d));
+                let b_leads_in = b.iter().any(|d| leads_in(*d));
+                if a_leads_in == b_leads_in {
+                    errors.insert(TrackError::AmbiguousCurve { coord: *coord });
+                }
+            }
+
+            // Every direction a tile connects to must lead to a tile that connects back
+            let dangling = directions
+                .iter()
+                .filter(|direction| {
+                    neighbor(coord, **direction)
+                        .and_then(|n| connections.get(&n))
+                        .is_none_or(|ds| !ds.contains(&direction.reverse()))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if is_cart(c) && !dangling.is_empty() {
+                errors.insert(TrackError::CartOnInvalidTile { coord: *coord, cart: c });
+                continue;
+            }
+
+            // Track that runs into the side of a cart means the cart is sitting on a curve or an
+            // intersection, so blame the cart rather than the track
+            for direction in dangling {
+                let into_cart = neighbor(coord, direction)
+                    .and_then(|n| tiles.get(&n).map(|t| (n, *t)))
+                    .filter(|(_n, t)| is_cart(*t));
+                match into_cart {
+                    Some((n, t)) => {
+                        errors.insert(TrackError::CartOnInvalidTile { coord: n, cart: t })
+                    },
+                    None => errors.insert(TrackError::DanglingEnd { coord: *coord, direction }),
+                };
+            }
+        }
+
+        errors.into_iter().collect()
+    }
+
+    /// Draws the system in the same ASCII format System#parse reads, with carts drawn over the
+    /// track they're on
+    pub fn to_lines(&self) -> Vec<String> {
+        let height = self.nodes.keys().map(|coord| coord.y + 1).max().unwrap_or(0);
+        let width = self.nodes.keys().map(|coord| coord.x + 1).max().unwrap_or(0);
+        let mut lines = vec![vec![' '; width]; height];
+
+        for (coord, node) in self.nodes.iter() {
+            lines[coord.y][coord.x] = match node {
+                Node::StraightLr { .. } => '-',
+                Node::StraightUd { .. } => '|',
+                Node::CurveUl { .. } | Node::CurveDr { .. } => '/',
+                Node::CurveUr { .. } | Node::CurveDl { .. } => '\\',
+                Node::Intersection { .. } => '+',
+            };
+        }
+
+        for (coord, cart) in self.carts.iter() {
+            lines[coord.y][coord.x] = match cart.direction {
+                Direction::Up => '^',
+                Direction::Right => '>',
+                Direction::Down => 'v',
+                Direction::Left => '<',
+            };
+        }
+
+        lines
+            .iter()
+            .map(|line| line.iter().collect::<String>().trim_end().to_string())
+            .collect()
+    }
+}
+
+/// A problem with ASCII tracks found by System#validate
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub enum TrackError {
+    DanglingEnd {
+        coord: NodeCoordinate,
+        direction: Direction,
+    },
+    CartOnInvalidTile {
+        coord: NodeCoordinate,
+        cart: char,
+    },
+    AmbiguousCurve {
+        coord: NodeCoordinate,
+    },
+    UnknownTile {
+        coord: NodeCoordinate,
+        tile: char,
+    },
+}
+
+impl fmt::Display for TrackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrackError::DanglingEnd { coord, direction } => {
+                write!(f, "{},{}: track going {:?} doesn't connect", coord.x, coord.y, direction)
+            },
+            TrackError::CartOnInvalidTile { coord, cart } => {
+                write!(f, "{},{}: cart '{}' isn't on matching track", coord.x, coord.y, cart)
+            },
+            TrackError::AmbiguousCurve { coord } => {
+                write!(f, "{},{}: curve could go either way", coord.x, coord.y)
+            },
+            TrackError::UnknownTile { coord, tile } => {
+                write!(f, "{},{}: unknown tile '{}'", coord.x, coord.y, tile)
+            },
+        }
+    }
+}
+
+impl fmt::Display for System {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in self.to_lines() {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// The directions a tile connects to, given the tile to its right.  This mirrors System#parse, so
+/// carts are assumed to sit on straight track, and curves are decided by the tile to their right.
+fn tile_directions(c: char, next: Option<char>) -> Option<Vec<Direction>> {
+    let curve_goes_right = matches!(next, Some('-') | Some('+') | Some('>') | Some('<'));
+
+    let directions = match c {
+        '-' | '<' | '>' => vec![Direction::Left, Direction::Right],
+        '|' | '^' | 'v' => vec![Direction::Up, Direction::Down],
+        '+' => vec![Direction::Up, Direction::Right, Direction::Down, Direction::Left],
+        '/' if curve_goes_right => vec![Direction::Down, Direction::Right],
+        '/' => vec![Direction::Up, Direction::Left],
+        '\\' if curve_goes_right => vec![Direction::Up, Direction::Right],
+        '\\' => vec![Direction::Down, Direction::Left],
+        _ => return None,
+    };
+
+    Some(directions)
+}
+
+fn is_cart(c: char) -> bool {
+    c == '^' || c == 'v' || c == '<' || c == '>'
+}
+
+/// The coordinate one step in a direction, if it isn't off the top or left of the map
+fn neighbor(coord: &NodeCoordinate, direction: Direction) -> Option<NodeCoordinate> {
+    match direction {
+        Direction::Up if coord.y == 0 => None,
+        Direction::Left if coord.x == 0 => None,
+        _ => Some(System::next_coord(coord, &direction)),
+    }
 }
 
 #[cfg(test)]
@@ -355,15 +697,19 @@ mod tests {
         assert_eq!(
             system.carts[&NodeCoordinate { y: 0, x: 2 }],
             Cart {
+                id: 0,
                 direction: Direction::Right,
                 next_turn: TurnDirection::Left,
+                is_stopped: false,
             },
         );
         assert_eq!(
             system.carts[&NodeCoordinate { y: 3, x: 9 }],
             Cart {
+                id: 1,
                 direction: Direction::Down,
                 next_turn: TurnDirection::Left,
+                is_stopped: false,
             },
         );
 
@@ -379,15 +725,19 @@ mod tests {
         assert_eq!(
             system.carts[&NodeCoordinate { y: 0, x: 3 }],
             Cart {
+                id: 0,
                 direction: Direction::Right,
                 next_turn: TurnDirection::Left,
+                is_stopped: false,
             },
         );
         assert_eq!(
             system.carts[&NodeCoordinate { y: 4, x: 9 }],
             Cart {
+                id: 1,
                 direction: Direction::Right,
                 next_turn: TurnDirection::Straight,
+                is_stopped: false,
             },
         );
 
@@ -396,15 +746,19 @@ mod tests {
         assert_eq!(
             system.carts[&NodeCoordinate { y: 0, x: 4 }],
             Cart {
+                id: 0,
                 direction: Direction::Down,
                 next_turn: TurnDirection::Left,
+                is_stopped: false,
             },
         );
         assert_eq!(
             system.carts[&NodeCoordinate { y: 4, x: 10 }],
             Cart {
+                id: 1,
                 direction: Direction::Right,
                 next_turn: TurnDirection::Straight,
+                is_stopped: false,
             },
         );
 
@@ -416,15 +770,19 @@ mod tests {
         assert_eq!(
             system.carts[&NodeCoordinate { y: 2, x: 7 }],
             Cart {
+                id: 1,
                 direction: Direction::Down,
                 next_turn: TurnDirection::Right,
+                is_stopped: false,
             },
         );
         assert_eq!(
             system.carts[&NodeCoordinate { y: 4, x: 7 }],
             Cart {
+                id: 0,
                 direction: Direction::Up,
                 next_turn: TurnDirection::Left,
+                is_stopped: false,
             },
         );
 
@@ -432,6 +790,121 @@ mod tests {
         assert_eq!(system.tick, 14);
     }
 
+    #[test]
+    fn test_system_next_tick_bounce() {
+        let mut system = System::parse(&vec![
+            "/---\\",
+            "|   |",
+            "\\>-</",
+        ]);
+        system.collision_policy = CollisionPolicy::Bounce;
+
+        // The right cart runs into the left cart, which has just moved to the middle
+        assert_eq!(system.next_tick().len(), 1);
+        assert_eq!(system.crashes, vec![Crash {
+            tick: 1,
+            coord: NodeCoordinate { y: 2, x: 2 },
+            cart_ids: (1, 0),
+        }]);
+        assert_eq!(system.cart_coord(0), Some(NodeCoordinate { y: 2, x: 2 }));
+        assert_eq!(system.carts[&NodeCoordinate { y: 2, x: 2 }].direction, Direction::Left);
+        assert_eq!(system.cart_coord(1), Some(NodeCoordinate { y: 2, x: 3 }));
+        assert_eq!(system.carts[&NodeCoordinate { y: 2, x: 3 }].direction, Direction::Right);
+
+        // Both carts head back the way they came
+        assert!(system.next_tick().is_empty());
+        assert_eq!(system.cart_coord(0), Some(NodeCoordinate { y: 2, x: 1 }));
+        assert_eq!(system.cart_coord(1), Some(NodeCoordinate { y: 2, x: 4 }));
+        assert_eq!(system.crashes_of(0).len(), 1);
+    }
+
+    #[test]
+    fn test_system_find_cycle() {
+        let mut system = System::parse(&vec![
+            "/>\\",
+            "| |",
+            "\\-/",
+        ]);
+        let forecast = system.find_cycle(100).unwrap();
+        assert_eq!(forecast.start, 0);
+        assert_eq!(forecast.period, 8);
+        assert_eq!(forecast.cart_coord_at(0, 3), Some(NodeCoordinate { y: 2, x: 2 }));
+        assert_eq!(
+            forecast.cart_coord_at(0, 1_000_000_000_000),
+            Some(NodeCoordinate { y: 0, x: 1 }),
+        );
+        assert_eq!(forecast.cart_coord_at(1, 3), None);
+
+        // The last cart keeps going after the others have crashed
+        let mut system = System::parse(&crate::tests::test_input_2());
+        let forecast = system.find_cycle(1000).unwrap();
+        let mut expected = System::parse(&crate::tests::test_input_2());
+        while expected.tick < 500 {
+            expected.next_tick();
+        }
+        assert_eq!(forecast.carts_at(500), Some(&expected.carts));
+        assert_eq!(forecast.carts_at(500).unwrap().len(), 1);
+
+        // Give up if the state doesn't repeat in time
+        let mut system = System::parse(&crate::tests::test_input_2());
+        assert!(system.find_cycle(2).is_none());
+    }
+
+    #[test]
+    fn test_system_validate() {
+        assert_eq!(System::validate(&crate::tests::test_input_2()), vec![]);
+
+        let errors = System::validate(&vec![
+            "/--\\",
+            "|  |",
+            "|  <",
+            "\\--/ -/-",
+            "  x",
+        ]);
+        assert_eq!(errors, vec![
+            TrackError::DanglingEnd {
+                coord: NodeCoordinate { y: 3, x: 5 },
+                direction: Direction::Right,
+            },
+            TrackError::DanglingEnd {
+                coord: NodeCoordinate { y: 3, x: 5 },
+                direction: Direction::Left,
+            },
+            TrackError::DanglingEnd {
+                coord: NodeCoordinate { y: 3, x: 6 },
+                direction: Direction::Down,
+            },
+            TrackError::DanglingEnd {
+                coord: NodeCoordinate { y: 3, x: 7 },
+                direction: Direction::Right,
+            },
+            TrackError::CartOnInvalidTile { coord: NodeCoordinate { y: 2, x: 3 }, cart: '<' },
+            TrackError::AmbiguousCurve { coord: NodeCoordinate { y: 3, x: 6 } },
+            TrackError::UnknownTile { coord: NodeCoordinate { y: 4, x: 2 }, tile: 'x' },
+        ]);
+        assert_eq!(format!("{}", errors[4]), "3,2: cart '<' isn't on matching track");
+    }
+
+    #[test]
+    fn test_system_to_lines() {
+        let input = crate::tests::test_input_2();
+        let mut system = System::parse(&input);
+        let trimmed = input.iter().map(|line| line.trim_end()).collect::<Vec<_>>();
+        assert_eq!(system.to_lines(), trimmed);
+
+        system.next_tick();
+        assert_eq!(format!("{}", system), [
+            "/---\\",
+            "|   |",
+            "| v-+-\\",
+            "| | | |",
+            "\\-+-/ |",
+            "  |   |",
+            "  ^---^",
+            "",
+        ].join("\n"));
+    }
+
     #[test]
     fn test_node_coordinate_ordering() {
         let mut coords = vec![
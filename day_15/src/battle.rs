@@ -157,6 +157,7 @@ impl fmt::Debug for PlayerType {
 
 
 /// MAP
+#[derive(Clone)]
 pub struct Map {
     pub players: Players,
     pub grid: Grid,
@@ -2,8 +2,13 @@
 #![feature(slice_sort_by_cached_key)]
 
 mod battle;
+mod replay;
+
+use std::env;
+use std::io::{self, BufRead};
 
 use crate::battle::{Battle, Map, PlayerType};
+use crate::replay::Replay;
 
 fn main() {
     let input = include_str!("input.txt")
@@ -11,6 +16,11 @@ fn main() {
         .split("\n")
         .collect();
 
+    // `cargo run -- replay` steps through the part 1 battle round by round
+    if env::args().nth(1).as_deref() == Some("replay") {
+        return replay(&input);
+    }
+
     // I can't figure out why the answer requires 78 rounds, even though the battle definitely ends
     // partway through the 80th round...
     println!("part_1: {}", part_1(&input) / 79 * 78);
@@ -72,6 +82,51 @@ fn part_2(lines: &Vec<&str>) -> usize {
     battle.outcome()
 }
 
+/// Records the battle and reads commands from stdin: "n" (next round), "p" (previous round),
+/// "<N>" (jump to round N), "d <A> <B>" (diff rounds A and B) and "q" (quit)
+fn replay(lines: &Vec<&str>) {
+    let mut battle = Battle {
+        map: Map::parse(lines),
+        round: 0,
+        is_finished: false,
+    };
+    let mut replay = Replay::record(&mut battle);
+
+    println!("\nRecorded {} rounds\n{}", replay.frames.len() - 1, replay.current());
+
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = line.unwrap();
+        let words = line.split_whitespace().collect::<Vec<_>>();
+
+        match words.as_slice() {
+            ["q"] => break,
+            ["n"] => println!("{}", replay.step_forward()),
+            ["p"] => println!("{}", replay.step_back()),
+            ["d", a, b] => {
+                let diff = a.parse().ok()
+                    .zip(b.parse().ok())
+                    .and_then(|(a, b)| replay.render_diff(a, b));
+                match diff {
+                    Some(diff) => println!("{}", diff),
+                    None => println!("No such rounds"),
+                }
+            },
+            [n] if n.parse::<usize>().is_ok() => {
+                let n = n.parse().unwrap();
+                match replay.render(n) {
+                    Some(frame) => {
+                        replay.seek(n);
+                        println!("{}", frame);
+                    },
+                    None => println!("No such round"),
+                }
+            },
+            _ => println!("Commands: n, p, <N>, d <A> <B>, q"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
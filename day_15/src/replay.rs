@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::battle::{Battle, Map, Player, PlayerType, Point};
+
+/// A recording of every round of a battle.  Frame 0 is the starting map, frame N is the map after
+/// the Nth call to Battle#play_round.  The last frame may be a round that ended partway through.
+pub struct Replay {
+    pub frames: Vec<Frame>,
+    pub cursor: usize,
+}
+
+pub struct Frame {
+    pub round: usize,
+    pub is_finished: bool,
+    pub map: Map,
+}
+
+impl Replay {
+    /// Plays the battle to completion, snapshotting the map after each round
+    pub fn record(battle: &mut Battle) -> Replay {
+        let mut frames = vec![Frame::of(battle)];
+
+        while !battle.is_finished {
+            battle.play_round();
+            frames.push(Frame::of(battle));
+        }
+
+        Replay { frames, cursor: 0 }
+    }
+
+    pub fn current(&self) -> &Frame {
+        &self.frames[self.cursor]
+    }
+
+    /// Moves to frame n, clamped to the last frame
+    pub fn seek(&mut self, n: usize) -> &Frame {
+        self.cursor = n.min(self.frames.len() - 1);
+        self.current()
+    }
+
+    pub fn step_forward(&mut self) -> &Frame {
+        self.seek(self.cursor + 1)
+    }
+
+    pub fn step_back(&mut self) -> &Frame {
+        self.seek(self.cursor.saturating_sub(1))
+    }
+
+    /// Renders frame n, or None if it wasn't recorded
+    pub fn render(&self, n: usize) -> Option<String> {
+        self.frames.get(n).map(|frame| format!("{}", frame))
+    }
+
+    /// Changes to every unit that moved, took damage or died between frames a and b
+    pub fn diff(&self, a: usize, b: usize) -> Option<Vec<UnitDiff>> {
+        let before = &self.frames.get(a)?.map;
+        let after = &self.frames.get(b)?.map;
+
+        let after_by_id = after.players
+            .iter()
+            .map(|(point, player)| (player.id, (*point, player)))
+            .collect::<BTreeMap<_, _>>();
+
+        let diffs = before.players
+            .iter()
+            .filter_map(|(point, player)| {
+                let diff = match after_by_id.get(&player.id) {
+                    Some((to, after_player)) => UnitDiff {
+                        id: player.id,
+                        player_type: player.player_type.clone(),
+                        from: *point,
+                        to: Some(*to),
+                        hp_before: player.hp,
+                        hp_after: after_player.hp,
+                    },
+                    None => UnitDiff {
+                        id: player.id,
+                        player_type: player.player_type.clone(),
+                        from: *point,
+                        to: None,
+                        hp_before: player.hp,
+                        hp_after: 0,
+                    },
+                };
+                if diff.is_moved() || diff.is_damaged() || diff.is_dead() {
+                    Some(diff)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Some(diffs)
+    }
+
+    /// Renders frame b with units that changed since frame a drawn in lowercase, followed by a
+    /// line per changed unit
+    pub fn render_diff(&self, a: usize, b: usize) -> Option<String> {
+        let diffs = self.diff(a, b)?;
+        let frame = &self.frames[b];
+        let changed = diffs
+            .iter()
+            .filter_map(|diff| diff.to.map(|to| (to, diff)))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut out = format!("{} -> {}\n", self.frames[a].title(), frame.title());
+        out.push_str(&render_map(&frame.map, |point, player| {
+            let ch = format!("{:?}", player.player_type);
+            if changed.contains_key(point) { ch.to_lowercase() } else { ch }
+        }));
+        for diff in diffs.iter() {
+            out.push_str(&format!("{}\n", diff));
+        }
+
+        Some(out)
+    }
+}
+
+impl Frame {
+    fn of(battle: &Battle) -> Frame {
+        Frame {
+            round: battle.round,
+            is_finished: battle.is_finished,
+            map: battle.map.clone(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        if self.is_finished {
+            format!("Finished partway through round {}", self.round + 1)
+        } else if self.round == 0 {
+            String::from("Initially")
+        } else {
+            format!("After {} rounds", self.round)
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}:", self.title())?;
+        write!(f, "{}", render_map(&self.map, |_point, player| format!("{:?}", player.player_type)))
+    }
+}
+
+/// Draws the map in the puzzle's notation, with the hit points of each row's units listed beside it
+fn render_map<F>(map: &Map, draw_player: F) -> String
+    where F: Fn(&Point, &Player) -> String
+{
+    let mut out = String::new();
+    let mut rows: BTreeMap<isize, (String, Vec<String>)> = BTreeMap::new();
+
+    for (point, is_wall) in map.grid.iter() {
+        let (line, units) = rows.entry(point.row).or_insert_with(|| (String::new(), Vec::new()));
+
+        if *is_wall {
+            line.push('#');
+        } else if let Some(player) = map.players.get(point) {
+            line.push_str(&draw_player(point, player));
+            units.push(format!("{:?}({})", player.player_type, player.hp));
+        } else {
+            line.push('.');
+        }
+    }
+
+    for (line, units) in rows.values() {
+        if units.is_empty() {
+            out.push_str(&format!("{}\n", line));
+        } else {
+            out.push_str(&format!("{}   {}\n", line, units.join(", ")));
+        }
+    }
+
+    out
+}
+
+
+/// UNIT DIFF
+#[derive(Eq, PartialEq, Debug)]
+pub struct UnitDiff {
+    pub id: usize,
+    pub player_type: PlayerType,
+    pub from: Point,
+    pub to: Option<Point>,
+    pub hp_before: isize,
+    pub hp_after: isize,
+}
+
+impl UnitDiff {
+    pub fn is_moved(&self) -> bool {
+        self.to.is_some_and(|to| to != self.from)
+    }
+
+    pub fn is_damaged(&self) -> bool {
+        self.hp_after < self.hp_before
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.to.is_none()
+    }
+}
+
+impl fmt::Display for UnitDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} {}", self.player_type, self.id)?;
+        match self.to {
+            None => write!(f, " died at {}", self.from),
+            Some(to) => {
+                if self.is_moved() {
+                    write!(f, " moved {} -> {}", self.from, to)?;
+                }
+                if self.is_damaged() {
+                    write!(f, " hp {} -> {}", self.hp_before, self.hp_after)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::pt;
+
+    fn example_replay() -> Replay {
+        let mut battle = Battle {
+            map: Map::parse(&super::super::tests::example_1()),
+            round: 0,
+            is_finished: false,
+        };
+        Replay::record(&mut battle)
+    }
+
+    #[test]
+    fn test_replay_record() {
+        let replay = example_replay();
+        assert_eq!(replay.frames.len(), 49);
+        assert_eq!(replay.frames[0].round, 0);
+        assert_eq!(replay.frames[47].round, 47);
+        assert!(replay.frames[48].is_finished);
+    }
+
+    #[test]
+    fn test_replay_render() {
+        let replay = example_replay();
+        assert_eq!(replay.render(2).unwrap(), [
+            "After 2 rounds:",
+            "#######",
+            "#...G.#   G(200)",
+            "#..GEG#   G(200), E(188), G(194)",
+            "#.#.#G#   G(194)",
+            "#...#E#   E(194)",
+            "#.....#",
+            "#######",
+            "",
+        ].join("\n"));
+        assert_eq!(replay.render(49), None);
+    }
+
+    #[test]
+    fn test_replay_step() {
+        let mut replay = example_replay();
+        assert_eq!(replay.step_back().round, 0);
+        assert_eq!(replay.step_forward().round, 1);
+        assert_eq!(replay.step_forward().round, 2);
+        assert_eq!(replay.step_back().round, 1);
+        assert!(replay.seek(1000).is_finished);
+        assert!(replay.step_forward().is_finished);
+    }
+
+    #[test]
+    fn test_replay_diff() {
+        let replay = example_replay();
+
+        // Round 1: the top goblin moves, and four units trade blows
+        let diffs = replay.diff(0, 1).unwrap();
+        assert_eq!(diffs[0], UnitDiff {
+            id: 0,
+            player_type: PlayerType::Goblin,
+            from: pt(1, 2),
+            to: Some(pt(1, 3)),
+            hp_before: 200,
+            hp_after: 200,
+        });
+        assert_eq!(diffs.iter().filter(|d| d.is_moved()).count(), 2);
+        assert_eq!(diffs.iter().filter(|d| d.is_damaged()).count(), 4);
+
+        // The elf at [2, 4] dies between rounds 2 and 28
+        let diffs = replay.diff(2, 28).unwrap();
+        assert!(diffs.iter().any(|d| d.is_dead() && d.player_type == PlayerType::Elf));
+
+        assert_eq!(replay.diff(0, 100), None);
+    }
+
+    #[test]
+    fn test_replay_render_diff() {
+        let replay = example_replay();
+        assert_eq!(replay.render_diff(0, 1).unwrap(), [
+            "Initially -> After 1 rounds",
+            "#######",
+            "#..g..#   G(200)",
+            "#...eg#   E(197), G(197)",
+            "#.#g#g#   G(200), G(197)",
+            "#...#e#   E(197)",
+            "#.....#",
+            "#######",
+            "G 0 moved [1, 2] -> [1, 3]",
+            "E 1 hp 200 -> 197",
+            "G 2 hp 200 -> 197",
+            "G 3 hp 200 -> 197",
+            "G 4 moved [4, 3] -> [3, 3]",
+            "E 5 hp 200 -> 197",
+            "",
+        ].join("\n"));
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::ground::{Coordinate, Ground, Material, Material::*};
+
+/// Ground#left or Ground#right
+type Neighbour = for<'a> fn(&'a Ground, &Coordinate) -> (Coordinate, Option<&'a Material>);
+
+/// Water running down from the springs, one wave at a time.  Each wave moves every source of
+/// water along by one step: dripping down a tile, or spreading out across a layer.
+///
+/// Every tile of water is either flowing (it can still run somewhere) or settled (it's trapped).
+/// A layer only settles once it has clay at both ends and clay or settled water under every tile,
+/// and when it does the water backs up into the tile that fed it, which spreads again.
+#[derive(Debug)]
+pub struct Flow {
+    pub ground: Ground,
+    pub wave: usize,
+    sources: BTreeSet<Coordinate>,
+    /// Where each source's water came from, to back up into once it settles
+    fed_by: HashMap<Coordinate, Coordinate>,
+}
+
+impl Flow {
+    pub fn new(ground: Ground) -> Flow {
+        let sources = ground.springs.iter().cloned().collect();
+
+        Flow {
+            ground,
+            wave: 0,
+            sources,
+            fed_by: HashMap::new(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Runs waves until the water can't go anywhere new
+    pub fn run(&mut self) {
+        while !self.is_finished() {
+            self.step();
+        }
+    }
+
+    /// Advances every source by one wave.  Returns false once there's nothing left to advance.
+    pub fn step(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+
+        let sources = std::mem::take(&mut self.sources);
+        for source in &sources {
+            self.advance(source);
+        }
+        self.wave += 1;
+
+        !self.is_finished()
+    }
+
+    fn advance(&mut self, source: &Coordinate) {
+        // Settled already, by water coming in from elsewhere, so keep backing up
+        if self.ground.coordinates.get(source) == Some(&Water) {
+            return self.back_up(source);
+        }
+
+        if !self.ground.is_spring(source) {
+            self.ground.coordinates.insert(source.clone(), FlowingWater);
+        }
+
+        // Past the lowest clay the water runs off forever
+        if source.y >= self.ground.max_y {
+            return;
+        }
+
+        let (below_coord, below_mat) = self.ground.below(source);
+        match below_mat {
+            // Drip
+            None => {
+                self.fed_by.insert(below_coord.clone(), source.clone());
+                self.sources.insert(below_coord);
+            },
+            // Another stream has already been this way
+            Some(FlowingWater) => (),
+            Some(Clay) | Some(Water) => self.spread(source),
+        }
+    }
+
+    /// Spreads across the layer the source sits on, until it hits clay or runs off an edge
+    fn spread(&mut self, source: &Coordinate) {
+        let (left_coord, left_edge) = self.spread_to(source, Ground::left);
+        let (right_coord, right_edge) = self.spread_to(source, Ground::right);
+        let settled = !left_edge && !right_edge;
+
+        // Springs stay springs, even when water spreads across them
+        for x in left_coord.x..=right_coord.x {
+            let coord = Coordinate { y: source.y, x };
+            if !self.ground.is_spring(&coord) {
+                let water = if settled { Water } else { FlowingWater };
+                self.ground.coordinates.insert(coord, water);
+            }
+        }
+
+        if settled {
+            return self.back_up(source);
+        }
+
+        // Each edge drips down, and backs up into whatever fed this layer if it ever settles
+        for (edge_coord, is_edge) in [(left_coord, left_edge), (right_coord, right_edge)] {
+            if is_edge {
+                if let Some(fed_by) = self.fed_by.get(source).cloned() {
+                    self.fed_by.entry(edge_coord.clone()).or_insert(fed_by);
+                }
+                self.sources.insert(edge_coord);
+            }
+        }
+    }
+
+    /// Walks along the source's layer, returning the last tile reached and whether it's an edge
+    /// the water drips off (rather than a tile next to clay)
+    fn spread_to(&self, source: &Coordinate, next: Neighbour) -> (Coordinate, bool) {
+        let mut coord = source.clone();
+        loop {
+            if !self.is_supported(&coord) {
+                return (coord, true);
+            }
+
+            let (next_coord, next_mat) = next(&self.ground, &coord);
+            if next_mat == Some(&Clay) {
+                return (coord, false);
+            }
+            coord = next_coord;
+        }
+    }
+
+    /// Whether water here can spread sideways: only clay and settled water hold it up, so water
+    /// landing on a stream runs along it rather than across it
+    fn is_supported(&self, coordinate: &Coordinate) -> bool {
+        matches!(self.ground.below(coordinate).1, Some(Clay) | Some(Water))
+    }
+
+    /// The source's layer has settled, so the water rises into the tile that fed it
+    fn back_up(&mut self, source: &Coordinate) {
+        if let Some(fed_by) = self.fed_by.get(source) {
+            self.sources.insert(fed_by.clone());
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::BTreeMap;
+use std::fmt;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Ground {
     pub coordinates: BTreeMap<Coordinate, Material>,
-    pub spring: Coordinate,
+    pub springs: Vec<Coordinate>,
     pub min_y: usize,
     pub max_y: usize,
 }
@@ -18,7 +19,7 @@ pub struct Coordinate {
     pub x: usize,
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub enum Material {
     Clay,
     Water,
@@ -28,13 +29,17 @@ pub enum Material {
 pub type Veins<'a> = Vec<&'a str>;
 
 impl Ground {
+    /// Parses the veins of clay, with the puzzle's one spring at x=500, y=0
     pub fn parse(veins: &Veins) -> Ground {
+        Ground::parse_with_springs(veins, vec![Coordinate { y: 0, x: 500 }])
+    }
+
+    pub fn parse_with_springs(veins: &Veins, springs: Vec<Coordinate>) -> Ground {
         lazy_static! {
             static ref VEIN_REGEX: Regex = Regex::new(r"(\w)=(\d+), (\w)=(\d+)\.\.(\d+)").unwrap();
         }
 
         let mut coordinates = BTreeMap::new();
-        let spring = Coordinate { y: 0, x: 500 };
 
         for vein in veins {
             let cap = VEIN_REGEX.captures(&vein).unwrap();
@@ -61,16 +66,35 @@ impl Ground {
 
         Ground {
             coordinates,
-            spring,
+            springs,
             min_y,
             max_y,
         }
     }
 
-    pub fn above(&self, coordinate: &Coordinate) -> (Coordinate, Option<&Material>) {
-        let above_coord = Coordinate { y: coordinate.y - 1, x: coordinate.x };
-        let above_mat = self.coordinates.get(&above_coord);
-        (above_coord, above_mat)
+    pub fn is_spring(&self, coordinate: &Coordinate) -> bool {
+        self.springs.contains(coordinate)
+    }
+
+    /// Number of tiles the water reaches, not counting any above the highest clay.  Springs count
+    /// too when they're in range, since the water starts there.
+    pub fn reachable(&self) -> usize {
+        let in_range = |coord: &Coordinate| coord.y >= self.min_y && coord.y <= self.max_y;
+        let water = self
+            .coordinates
+            .iter()
+            .filter(|(coord, mat)| **mat != Material::Clay && in_range(coord))
+            .count();
+
+        water + self.springs.iter().filter(|spring| in_range(spring)).count()
+    }
+
+    /// Number of tiles of settled water, not counting springs
+    pub fn settled(&self) -> usize {
+        self.coordinates
+            .iter()
+            .filter(|(coord, mat)| **mat == Material::Water && !self.is_spring(coord))
+            .count()
     }
 
     pub fn below(&self, coordinate: &Coordinate) -> (Coordinate, Option<&Material>) {
@@ -90,6 +114,43 @@ impl Ground {
         let right_mat = self.coordinates.get(&right_coord);
         (right_coord, right_mat)
     }
+
+    /// Draws the tiles from top_left to bottom_right (inclusive) the way the puzzle does: `+` for
+    /// the spring, `#` clay, `~` settled water, `|` flowing water and `.` sand
+    pub fn render(&self, top_left: &Coordinate, bottom_right: &Coordinate) -> String {
+        let mut map = String::new();
+
+        for y in top_left.y..=bottom_right.y {
+            for x in top_left.x..=bottom_right.x {
+                let coordinate = Coordinate { y, x };
+                map.push(match self.coordinates.get(&coordinate) {
+                    _ if self.is_spring(&coordinate) => '+',
+                    Some(Material::Clay) => '#',
+                    Some(Material::Water) => '~',
+                    Some(Material::FlowingWater) => '|',
+                    None => '.',
+                });
+            }
+            map.push('\n');
+        }
+
+        map
+    }
+}
+
+/// Renders from the highest spring down to the lowest clay, with a column of sand either side so
+/// water running off the edges shows
+impl fmt::Display for Ground {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let xs = self.coordinates.keys().chain(&self.springs).map(|c| c.x);
+        let min_x = xs.clone().min().unwrap();
+        let max_x = xs.max().unwrap();
+        let min_y = self.springs.iter().map(|c| c.y).min().unwrap_or(self.min_y);
+
+        let top_left = Coordinate { y: min_y, x: min_x.saturating_sub(1) };
+        let bottom_right = Coordinate { y: self.max_y, x: max_x + 1 };
+        write!(f, "{}", self.render(&top_left, &bottom_right))
+    }
 }
 
 #[cfg(test)]
@@ -1,15 +1,15 @@
 #![feature(dbg_macro)]
 #![feature(map_get_key_value)]
 
+mod flow;
 mod ground;
 
 extern crate lazy_static;
 extern crate regex;
 
-use crate::ground::{Ground, Veins, Material::*};
-use std::collections::BTreeSet;
-use std::collections::HashSet;
-use crate::ground::Coordinate;
+use crate::flow::Flow;
+use crate::ground::{Coordinate, Ground, Veins};
+use std::env;
 
 fn main() {
     let veins = include_str!("input.txt")
@@ -17,141 +17,80 @@ fn main() {
         .split("\n")
         .collect();
 
+    // `cargo run -- render [waves] [x y width height]` draws the ground after that many waves of
+    // water, optionally cropped to a window
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.first().map(String::as_str) == Some("render") {
+        let numbers = args[1..].iter().map(|arg| arg.parse().unwrap()).collect::<Vec<usize>>();
+        let waves = numbers.first().cloned().unwrap_or(usize::MAX);
+        return print!("{}", render(&veins, waves, numbers.get(1..5)));
+    }
+
+    // `cargo run -- springs <x,y> [<x,y> ...]` counts the water from springs in those places
+    if args.first().map(String::as_str) == Some("springs") {
+        let springs = args[1..]
+            .iter()
+            .map(|arg| {
+                let xy = arg.split(',').map(|n| n.parse().unwrap()).collect::<Vec<usize>>();
+                Coordinate { y: xy[1], x: xy[0] }
+            })
+            .collect();
+        let ground = Ground::parse_with_springs(&veins, springs);
+
+        for (spring, reachable, settled) in per_spring(&ground) {
+            println!("{},{}: {} reachable, {} settled", spring.x, spring.y, reachable, settled);
+        }
+        let ground = run_water(ground).ground;
+        return println!("all: {} reachable, {} settled", ground.reachable(), ground.settled());
+    }
+
     println!("part_1: {}", reachable_tiles(&veins));
     println!("part_2: {}", stable_tiles(&veins));
 }
 
 // Part 1
 fn reachable_tiles(veins: &Veins) -> usize {
-    let mut ground = Ground::parse(veins);
-    let visited = run_water(&mut ground);
-
-    visited
-        .iter()
-        .filter(|s| s.y >= ground.min_y && s.y <= ground.max_y)
-        .count()
+    run_water(Ground::parse(veins)).ground.reachable()
 }
 
 // Part 2
 fn stable_tiles(veins: &Veins) -> usize {
-    let mut ground = Ground::parse(veins);
-    run_water(&mut ground);
-
-    ground.coordinates
-        .iter()
-        .filter(|(_coord, mat)| mat == &&Water)
-        .count()
+    run_water(Ground::parse(veins)).ground.settled()
 }
 
-fn run_water(ground: &mut Ground) -> BTreeSet<Coordinate> {
-    let mut visited = BTreeSet::new();
-    let mut sources = HashSet::new();
-    sources.insert(ground.spring.clone());
-
-    loop {
-        let mut next_sources = HashSet::new();
-
-        for source in sources.iter() {
-            visited.insert(source.clone());
-
-            let (below_coord, below_mat) = ground.below(source);
-
-            if below_mat.is_none() {
-                // Below is empty, so drip
-                next_sources.insert(below_coord.clone());
-                continue;
-            } else {
-                // Below this source is a Material:
-                //   - If below is FlowingWater, then just stop
-                //   - Otherwise, spread out the water
-                //     - If overflow, this layer is FlowingWater and produces new source(s)
-                //     - If no overflow, this layer is Water and moves the source up
-
-                // If there is FlowingWater below this, then we can stop following this source
-                // right now because it has already been explored
-                if *below_mat.unwrap() == FlowingWater {
-                    continue;
-                }
-
-                // This will be mutated if this source causes an overflow
-                let mut overflowed = false;
-
-                // Get leftmost legal coordinate to spread to
-                let (mut left_coord, mut _left_mat) = (source.clone(), None);
-                loop {
-                    // Stop if this is an overflow position
-                    if ground.below(&left_coord).1.is_none() {
-                        next_sources.insert(left_coord.clone());
-                        overflowed = true;
-                        break;
-                    }
-
-                    // Stop if the left is clay
-                    if let Some(next_left) = ground.left(&left_coord).1 {
-                        if *next_left == Clay {
-                            break;
-                        }
-                    }
-
-                    // Otherwise, keep going left
-                    let next_left_coord = ground.left(&left_coord);
-                    left_coord = next_left_coord.0;
-                    _left_mat = next_left_coord.1;
-                }
-
-                // Get rightmost legal coordinate to spread to
-                let (mut right_coord, mut _right_mat) = (source.clone(), None);
-                loop {
-                    // Stop if this is an overflow position
-                    if ground.below(&right_coord).1.is_none() {
-                        next_sources.insert(right_coord.clone());
-                        overflowed = true;
-                        break;
-                    }
-
-                    // Stop if the right is clay
-                    if let Some(next_right) = ground.right(&right_coord).1 {
-                        if *next_right == Clay {
-                            break;
-                        }
-                    }
-
-                    // Otherwise, keep going right
-                    let next_right_coord = ground.right(&right_coord);
-                    right_coord = next_right_coord.0;
-                    _right_mat = next_right_coord.1;
-                }
-
-                // Fill it up
-                for x in left_coord.x..=right_coord.x {
-                    let coord = Coordinate { y: source.y, x };
-                    let water = if overflowed { FlowingWater } else { Water };
-
-                    ground.coordinates.insert(coord.clone(), water);
-                    visited.insert(coord.clone());
-                }
-
-                // If didn't overflow, then move the source up one, since we've filled this layer
-                // and can think of the coordinate above this one to be our new "source"
-                if !overflowed {
-                    next_sources.insert(ground.above(&source).0);
-                }
-            }
-        }
+fn run_water(ground: Ground) -> Flow {
+    let mut flow = Flow::new(ground);
+    flow.run();
+    flow
+}
 
-        // Prepare for next iteration through loop by filtering to only valid sources
-        sources = next_sources
-            .into_iter()
-            .filter(|s| s.y <= ground.max_y)
-            .collect();
+// The reachable and settled tiles for each spring, as if it were the only one.  Springs share
+// streams and fill basins for each other, so all of them together won't just be the sum.
+fn per_spring(ground: &Ground) -> Vec<(Coordinate, usize, usize)> {
+    ground.springs
+        .iter()
+        .map(|spring| {
+            let mut alone = ground.clone();
+            alone.springs = vec![spring.clone()];
+            let flow = run_water(alone);
+            (spring.clone(), flow.ground.reachable(), flow.ground.settled())
+        })
+        .collect()
+}
 
-        // Done if there are no valid sources left
-        if sources.len() == 0 {
-            break;
-        }
+// Draws the ground after the given number of waves, cropped to [x, y, width, height] if given
+fn render(veins: &Veins, waves: usize, window: Option<&[usize]>) -> String {
+    let mut flow = Flow::new(Ground::parse(veins));
+    while flow.wave < waves && flow.step() {}
+
+    match window {
+        Some([x, y, width, height]) => {
+            let top_left = Coordinate { y: *y, x: *x };
+            let bottom_right = Coordinate { y: y + height - 1, x: x + width - 1 };
+            flow.ground.render(&top_left, &bottom_right)
+        },
+        _ => format!("{}", flow.ground),
     }
-
-    visited
 }
 
 #[cfg(test)]
@@ -213,6 +152,198 @@ mod tests {
         assert_eq!(reachable_tiles(&test_input_1()), 57);
         assert_eq!(reachable_tiles(&test_input_2()), 34);
         assert_eq!(reachable_tiles(&test_input_3()), 72);
+
+        // The cup right under the spring fills up and spills across the spring's row, which
+        // leaves the spring itself alone
+        let flow = run_water(Ground::parse(&test_input_2()));
+        assert_eq!(flow.ground.coordinates.get(&Coordinate { y: 0, x: 500 }), None);
+        assert!(flow.ground.coordinates.contains_key(&Coordinate { y: 0, x: 501 }));
+    }
+
+    fn rendered(veins: &Veins) -> Vec<String> {
+        let flow = run_water(Ground::parse(veins));
+        format!("{}", flow.ground).lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_flow_nested() {
+        // A bucket sitting inside a bigger one, right under the spring.  The inner one fills
+        // first, spills both ways, and the outer one fills up around and then over it.
+        let veins = vec![
+            "x=494, y=2..10",
+            "x=506, y=2..10",
+            "y=10, x=494..506",
+            "x=498, y=5..8",
+            "x=502, y=5..8",
+            "y=8, x=498..502",
+        ];
+        let expected = vec![
+            "........+........",
+            ".|||||||||||||||.",
+            ".|#~~~~~~~~~~~#|.",
+            ".|#~~~~~~~~~~~#|.",
+            ".|#~~~~~~~~~~~#|.",
+            ".|#~~~#~~~#~~~#|.",
+            ".|#~~~#~~~#~~~#|.",
+            ".|#~~~#~~~#~~~#|.",
+            ".|#~~~#####~~~#|.",
+            ".|#~~~~~~~~~~~#|.",
+            ".|#############|.",
+        ];
+        assert_eq!(rendered(&veins), expected);
+    }
+
+    #[test]
+    fn test_flow_adjacent_overflow() {
+        // The bucket under the spring has a low wall, and spills over it into the next one
+        let veins = vec![
+            "x=496, y=3..8",
+            "x=502, y=5..8",
+            "x=506, y=3..8",
+            "y=8, x=496..506",
+        ];
+        let expected = vec![
+            "......+........",
+            "......|........",
+            ".|||||||||||||.",
+            ".|#~~~~~~~~~#|.",
+            ".|#~~~~~~~~~#|.",
+            ".|#~~~~~#~~~#|.",
+            ".|#~~~~~#~~~#|.",
+            ".|#~~~~~#~~~#|.",
+            ".|###########|.",
+        ];
+        assert_eq!(rendered(&veins), expected);
+    }
+
+    #[test]
+    fn test_flow_merging_streams() {
+        // A beam splits the stream in two, and both halves land in the same bucket
+        let veins = vec![
+            "y=3, x=499..501",
+            "x=495, y=5..8",
+            "x=505, y=5..8",
+            "y=8, x=495..505",
+        ];
+        let expected = vec![
+            ".......+.......",
+            ".......|.......",
+            ".....|||||.....",
+            ".....|###|.....",
+            ".|||||||||||||.",
+            ".|#~~~~~~~~~#|.",
+            ".|#~~~~~~~~~#|.",
+            ".|#~~~~~~~~~#|.",
+            ".|###########|.",
+        ];
+        assert_eq!(rendered(&veins), expected);
+    }
+
+    #[test]
+    fn test_flow_spill_onto_stream() {
+        // The left bucket spills onto the right one's wall, which splits the stream: one half
+        // runs off, and the other fills the right bucket and then runs off the far side
+        let veins = vec![
+            "x=497, y=2..4",
+            "x=501, y=2..4",
+            "y=4, x=497..501",
+            "x=502, y=6..8",
+            "x=506, y=6..8",
+            "y=8, x=502..506",
+        ];
+        let expected = vec![
+            ".....+........",
+            ".|||||||......",
+            ".|#~~~#|......",
+            ".|#~~~#|......",
+            ".|#####|......",
+            ".|....|||||||.",
+            ".|....|#~~~#|.",
+            ".|....|#~~~#|.",
+            ".|....|#####|.",
+        ];
+        assert_eq!(rendered(&veins), expected);
+    }
+
+    #[test]
+    fn test_springs() {
+        let springs = vec![Coordinate { y: 0, x: 500 }, Coordinate { y: 1, x: 496 }];
+        let ground = Ground::parse_with_springs(&test_input_1(), springs);
+
+        // On its own, the second spring fills the left side of the top bucket and then spills
+        // into the right side and over the edge
+        let counts = per_spring(&ground);
+        assert_eq!(counts[0], (Coordinate { y: 0, x: 500 }, 57, 29));
+        assert_eq!(counts[1], (Coordinate { y: 1, x: 496 }, 80, 35));
+
+        // Together, the first spring has already filled most of the top bucket, so the second
+        // only tops up the left side before running off it
+        let flow = run_water(ground);
+        let expected = vec![
+            ".......+.......",
+            ".||+||||.....#.",
+            ".|#~~#||||...#.",
+            ".|#~~#~~#|.....",
+            ".|#~~#~~#|.....",
+            ".|#~~~~~#|.....",
+            ".|#~~~~~#|.....",
+            ".|#######|.....",
+            ".|.......|.....",
+            ".|..|||||||||..",
+            ".|..|#~~~~~#|..",
+            ".|..|#~~~~~#|..",
+            ".|..|#~~~~~#|..",
+            ".|..|#######|..",
+            "",
+        ];
+        assert_eq!(format!("{}", flow.ground), expected.join("\n"));
+        // The second spring is below the highest clay, so its own tile counts as well
+        assert_eq!(flow.ground.reachable(), 57 + 23 + 1);
+        assert_eq!(flow.ground.settled(), 29 + 6);
+    }
+
+    #[test]
+    fn test_render() {
+        let expected = vec![
+            "......+.......",
+            "......|.....#.",
+            ".#..#||||...#.",
+            ".#..#~~#|.....",
+            ".#..#~~#|.....",
+            ".#~~~~~#|.....",
+            ".#~~~~~#|.....",
+            ".#######|.....",
+            "........|.....",
+            "...|||||||||..",
+            "...|#~~~~~#|..",
+            "...|#~~~~~#|..",
+            "...|#~~~~~#|..",
+            "...|#######|..",
+            "",
+        ];
+        assert_eq!(render(&test_input_1(), usize::MAX, None), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_render_stepwise() {
+        // Three waves drip the water down to just above the first bucket
+        let window = [498, 0, 5, 5];
+        let expected = ["..+..", "..|..", "#.|..", "#..#.", "#..#.", ""];
+        assert_eq!(render(&test_input_1(), 3, Some(&window)), expected.join("\n"));
+
+        // After seven waves the stream has hit the bottom of the bucket and filled one layer
+        let mut flow = Flow::new(Ground::parse(&test_input_1()));
+        while flow.wave < 7 {
+            assert!(flow.step());
+        }
+        let top_left = Coordinate { y: 4, x: 495 };
+        let bottom_right = Coordinate { y: 7, x: 501 };
+        let expected = ["#..#.|#", "#....|#", "#~~~~~#", "#######", ""];
+        assert_eq!(flow.ground.render(&top_left, &bottom_right), expected.join("\n"));
+
+        flow.run();
+        assert!(flow.is_finished());
+        assert!(!flow.step());
     }
 
     #[test]
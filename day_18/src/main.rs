@@ -1,6 +1,9 @@
 #![feature(dbg_macro)]
 
-use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::rc::Rc;
+
+use automaton::{Automaton, Boundary, CountRule, Neighbourhood, Repeat, State};
 
 use crate::Acre::*;
 
@@ -16,138 +19,119 @@ fn main() {
 
 // Multiply the number of wood acres by the number of lumberyards after the given amount of time
 fn resource_value(area: &mut Area, minutes: usize) -> usize {
-    // There is a steady-state pattern that emerges after ~1000 iterations
-    if minutes >= 1000 {
-        let pattern = vec![
-            202272, 207172, 208351, 211140, 212248, 219349, 218584, 218286, 213244, 210630, 205800,
-            205412, 201916, 193120, 189090, 190143, 187525, 190740, 189601, 195471, 195426, 199758,
-            198062, 201684, 200349, 202515, 203895, 204486,
-        ];
-        return pattern[(minutes - 1000) % pattern.len()];
-    }
-
-    let mut cached_next: HashMap<Grid, Grid> = HashMap::new();
-
-    while area.minute < minutes {
-        // This was used to find the pattern
-        if area.minute >= 1000 {
-            println!("{}, {}", area.resource_value(), area.minute);
-        }
-
-        let mut next_grid = BTreeMap::new();
-
-        if let Some(next) = cached_next.get(&area.grid) {
-            next_grid = next.clone();
-        } else {
-            for (coordinate, acre) in area.grid.iter() {
-                let counts = area.adjacent_counts(&coordinate);
-                let mut next_acre = *acre;
-
-                // An open acre will become filled with trees if three or more adjacent acres contained
-                // trees. Otherwise, nothing happens.
-                if *acre == Open {
-                    if counts.get(&Trees).unwrap_or(&0) >= &3 {
-                        next_acre = Trees;
-                    }
-                }
-
-                // An acre filled with trees will become a lumberyard if three or more adjacent acres
-                // were lumberyards. Otherwise, nothing happens.
-                if *acre == Trees {
-                    if counts.get(&Lumberyard).unwrap_or(&0) >= &3 {
-                        next_acre = Lumberyard;
-                    }
-                }
-
-                // An acre containing a lumberyard will remain a lumberyard if it was adjacent to at
-                // least one other lumberyard and at least one acre containing trees. Otherwise, it
-                // becomes open.
-                if *acre == Lumberyard {
-                    let lumberyard_count = counts.get(&Lumberyard).unwrap_or(&0);
-                    let trees_count = counts.get(&Trees).unwrap_or(&0);
-
-                    if lumberyard_count >= &1 && trees_count >= &1 {
-                        next_acre = Lumberyard;
-                    } else {
-                        next_acre = Open;
-                    }
-                }
-
-                next_grid.insert(*coordinate, next_acre);
-            }
-
-            cached_next.insert(area.grid.clone(), next_grid.clone());
-        }
-
-        area.minute += 1;
-        area.grid = next_grid;
-    }
-
+    area.skip_to(minutes);
     area.resource_value()
 }
 
+/// The lumber collection area, as a fixed-size automaton of acres
 #[derive(Debug)]
 struct Area {
-    pub minute: usize,
-    pub grid: Grid,
+    pub automaton: Automaton,
+    /// Set once the acres start repeating an earlier minute's layout
+    pub cycle: Option<Repeat>,
 }
 
 impl Area {
     pub fn parse(lines: &Vec<&str>) -> Area {
-        let mut grid = BTreeMap::new();
-
-        for (y, line) in lines.iter().enumerate() {
-            for (x, acre) in line.chars().enumerate() {
-                let coordinate = Coordinate {
-                    y: y as isize,
-                    x: x as isize,
-                };
-                let acre = match acre {
-                    '.' => Open,
-                    '|' => Trees,
-                    '#' => Lumberyard,
-                    _ => panic!("Invalid"),
-                };
-
-                grid.insert(coordinate, acre);
-            }
-        }
+        let rows = lines
+            .iter()
+            .map(|line| {
+                line.chars()
+                    .map(|acre| match acre {
+                        '.' => Open,
+                        '|' => Trees,
+                        '#' => Lumberyard,
+                        _ => panic!("Invalid"),
+                    } as State)
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+
+        let automaton = Automaton::totalistic(
+            &rows,
+            3,
+            Neighbourhood::moore(),
+            Boundary::Fixed,
+            Area::rule(),
+        );
 
         Area {
-            minute: 0,
-            grid,
+            automaton,
+            cycle: None,
         }
     }
 
-    pub fn adjacent_counts(&self, to_coordinate: &Coordinate) -> HashMap<Acre, usize> {
-        let mut counts = HashMap::new();
+    /// Only how many of each kind of acre are adjacent matters, so the rule works from counts
+    fn rule() -> CountRule {
+        Rc::new(|acre, adjacent| {
+            let count = |acre: Acre| adjacent[acre as usize];
 
-        for y in (to_coordinate.y - 1)..=(to_coordinate.y + 1) {
-            for x in (to_coordinate.x - 1)..=(to_coordinate.x + 1) {
-                if to_coordinate.y == y && to_coordinate.x == x {
-                    continue;
-                }
+            match Acre::from_state(acre) {
+                // An open acre will become filled with trees if three or more adjacent acres
+                // contained trees. Otherwise, nothing happens.
+                Open if count(Trees) >= 3 => Trees as State,
 
-                let check_coord = Coordinate { y, x };
+                // An acre filled with trees will become a lumberyard if three or more adjacent
+                // acres were lumberyards. Otherwise, nothing happens.
+                Trees if count(Lumberyard) >= 3 => Lumberyard as State,
 
-                if let Some(acre) = self.grid.get(&check_coord) {
-                    let count = counts.entry(*acre).or_insert(0);
-                    *count += 1;
-                }
+                // An acre containing a lumberyard will remain a lumberyard if it was adjacent to
+                // at least one other lumberyard and at least one acre containing trees.
+                // Otherwise, it becomes open.
+                Lumberyard if count(Lumberyard) >= 1 && count(Trees) >= 1 => Lumberyard as State,
+                Lumberyard => Open as State,
+
+                _ => acre,
             }
+        })
+    }
+
+    pub fn minute(&self) -> usize {
+        self.automaton.generation
+    }
+
+    /// Advances to the given minute.  The acres eventually repeat an earlier layout, so watch for
+    /// that on the way, and once it happens skip all the whole cycles left in one go.
+    pub fn skip_to(&mut self, minute: usize) {
+        if self.cycle.is_none() && minute > self.minute() {
+            self.cycle = self.automaton.run_until_repeat(minute - self.minute());
         }
 
-        counts
+        match self.cycle {
+            Some(cycle) => self.automaton.skip_to(minute, &cycle),
+            None => while self.minute() < minute {
+                self.automaton.step();
+            },
+        }
+    }
+
+    pub fn acre(&self, coordinate: &Coordinate) -> Acre {
+        Acre::from_state(self.automaton.get(coordinate.y, coordinate.x))
     }
 
     pub fn resource_value(&self) -> usize {
-        let acres_trees = self.grid.iter().filter(|(_c, a)| **a == Trees).count();
-        let acres_lumberyards = self.grid.iter().filter(|(_c, a)| **a == Lumberyard).count();
+        let acres_trees = self.automaton.count(Trees as State);
+        let acres_lumberyards = self.automaton.count(Lumberyard as State);
         acres_trees * acres_lumberyards
     }
 }
 
-type Grid = BTreeMap<Coordinate, Acre>;
+impl fmt::Display for Area {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for y in 0..self.automaton.height() as isize {
+            for x in 0..self.automaton.width() as isize {
+                let acre = match self.acre(&Coordinate { y, x }) {
+                    Open => '.',
+                    Trees => '|',
+                    Lumberyard => '#',
+                };
+                write!(f, "{}", acre)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
 
 #[derive(Eq, PartialEq, Hash, Ord, PartialOrd, Copy, Clone, Debug)]
 struct Coordinate {
@@ -157,9 +141,20 @@ struct Coordinate {
 
 #[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
 enum Acre {
-    Open,
-    Trees,
-    Lumberyard,
+    Open = 0,
+    Trees = 1,
+    Lumberyard = 2,
+}
+
+impl Acre {
+    pub fn from_state(state: State) -> Acre {
+        match state {
+            0 => Open,
+            1 => Trees,
+            2 => Lumberyard,
+            _ => unreachable!(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -181,23 +176,109 @@ mod tests {
         ]
     }
 
+    fn input() -> Vec<&'static str> {
+        include_str!("input.txt").trim().split("\n").collect()
+    }
+
     #[test]
     fn test_part_1() {
         let mut area = Area::parse(&example_1());
         assert_eq!(resource_value(&mut area, 10), 1147);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let mut area = Area::parse(&input());
         assert_eq!(resource_value(&mut area, 1000), 202272);
         assert_eq!(resource_value(&mut area, 1001), 207172);
         assert_eq!(resource_value(&mut area, 1002), 208351);
         assert_eq!(resource_value(&mut area, 10_000), 201916);
+        assert_eq!(area.cycle.unwrap().period, 28);
+
+        // The example's forest dies out completely, and then stays that way
+        let mut area = Area::parse(&example_1());
+        assert_eq!(resource_value(&mut area, 1_000_000_000), 0);
+        assert_eq!(area.cycle.unwrap().period, 1);
+    }
+
+    #[test]
+    fn test_area_skip_to() {
+        let mut stepped = Area::parse(&input());
+        while stepped.minute() < 700 {
+            stepped.automaton.step();
+        }
+
+        let mut skipped = Area::parse(&input());
+        skipped.skip_to(700);
+        assert_eq!(skipped.minute(), 700);
+        assert_eq!(format!("{}", skipped), format!("{}", stepped));
+    }
+
+    // One minute of the puzzle's rules, worked out acre by acre from the characters with none of
+    // the automaton's machinery, to check it against
+    fn reference_minute(acres: &[Vec<char>]) -> Vec<Vec<char>> {
+        let height = acres.len() as isize;
+        let width = acres[0].len() as isize;
+        let adjacent = |y: isize, x: isize, acre: char| {
+            let mut count = 0;
+            for ny in y - 1..=y + 1 {
+                for nx in x - 1..=x + 1 {
+                    let inside = ny >= 0 && ny < height && nx >= 0 && nx < width;
+                    if (ny, nx) != (y, x) && inside && acres[ny as usize][nx as usize] == acre {
+                        count += 1;
+                    }
+                }
+            }
+            count
+        };
+
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| match acres[y as usize][x as usize] {
+                        '.' if adjacent(y, x, '|') >= 3 => '|',
+                        '|' if adjacent(y, x, '#') >= 3 => '#',
+                        '#' if adjacent(y, x, '#') >= 1 && adjacent(y, x, '|') >= 1 => '#',
+                        '#' => '.',
+                        acre => acre,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_area_large() {
+        // Tile the example out to 1000x1000 acres, edges and all, and check every acre against
+        // the reference each minute
+        let lines = (0..1000)
+            .map(|y| example_1()[y % 10].repeat(100))
+            .collect::<Vec<_>>();
+        let mut area = Area::parse(&lines.iter().map(|line| line.as_str()).collect());
+        let mut acres = lines.iter().map(|line| line.chars().collect()).collect::<Vec<_>>();
+
+        for minute in 1..=3 {
+            area.skip_to(minute);
+            acres = reference_minute(&acres);
+
+            let rendered = format!("{}", area);
+            assert_eq!(rendered.lines().count(), acres.len());
+            for (y, line) in rendered.lines().enumerate() {
+                let expected = acres[y].iter().collect::<String>();
+                assert_eq!(line, expected, "minute {}, row {}", minute, y);
+            }
+        }
+        assert!(area.resource_value() > 0);
     }
 
     #[test]
     fn test_area_parse() {
         let area = Area::parse(&example_1());
 
-        assert_eq!(area.minute, 0);
-        assert_eq!(area.grid[&Coordinate { y: 0, x: 0 }], Open);
-        assert_eq!(area.grid[&Coordinate { y: 0, x: 1 }], Lumberyard);
-        assert_eq!(area.grid[&Coordinate { y: 2, x: 1 }], Trees);
+        assert_eq!(area.minute(), 0);
+        assert_eq!(area.acre(&Coordinate { y: 0, x: 0 }), Open);
+        assert_eq!(area.acre(&Coordinate { y: 0, x: 1 }), Lumberyard);
+        assert_eq!(area.acre(&Coordinate { y: 2, x: 1 }), Trees);
+        assert_eq!(format!("{}", area), example_1().join("\n") + "\n");
     }
 }
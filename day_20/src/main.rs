@@ -1,10 +1,28 @@
 mod maze;
+mod route;
+
+use std::env;
 
 use maze::*;
+use route::Route;
 
 fn main() {
     let input = include_str!("input.txt").trim().to_string();
 
+    if let Err(error) = Route::parse(&input) {
+        println!("{}", error);
+        return;
+    }
+
+    // `cargo run -- <render|dot|json>` prints the facility instead of solving it
+    match env::args().nth(1).as_deref() {
+        Some("render") => return print!("{}", Maze::new(&input)),
+        Some("dot") => return print!("{}", Maze::new(&input).to_dot()),
+        Some("json") => return println!("{}", Maze::new(&input).to_json()),
+        Some("distances") => return distances(&input, env::args().skip(2).collect()),
+        _ => (),
+    }
+
     println!("part_1: {}", part_1(&input));
     println!("part_2: {}", part_2(&input, 1000));
 }
@@ -22,6 +40,31 @@ fn part_2(line: &String, n_doors: usize) -> usize {
     maze.at_least_n_doors_away(n_doors)
 }
 
+// `cargo run -- distances [x y]` describes how far every room is from the given room, or from
+// where you start
+fn distances(line: &str, start: Vec<String>) {
+    let maze = Maze::new(line);
+    let from_start;
+    let distances = match start.as_slice() {
+        [x, y] => {
+            let start = (x.parse().unwrap(), y.parse().unwrap());
+            from_start = maze.distances_from(start);
+            match &from_start {
+                Some(distances) => distances,
+                None => return println!("No room at {:?}", start),
+            }
+        },
+        _ => maze.distances(),
+    };
+
+    let (furthest, doors) = distances.furthest();
+    println!("Furthest room: {:?}, {} doors away", furthest, doors);
+    println!("Route: {}", distances.route_to(furthest).unwrap());
+    for (doors, rooms) in distances.histogram().iter().enumerate() {
+        println!("{: >5} doors: {} rooms", doors, rooms);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,6 +98,117 @@ mod tests {
         assert_eq!(part_1(&example_5()), 31);
     }
 
+    #[test]
+    fn test_maze_branches() {
+        // Every option of a branch carries on with the rest of the route
+        let maze = Maze::new("^(N|S)E$");
+        assert_eq!(maze.doors().len(), 4);
+        assert_eq!(maze.most_doors(), 2);
+
+        let maze = Maze::new("^(N|S)(E|W)N$");
+        assert_eq!(maze.doors().len(), 10);
+        assert_eq!(maze.most_doors(), 3);
+
+        // Detours come back to where they started
+        let maze = Maze::new("^N(EW|)N$");
+        assert_eq!(maze.doors().len(), 3);
+        assert_eq!(maze.most_doors(), 2);
+
+        let maze = Maze::new("^N(E(NS|)W|)N$");
+        assert_eq!(maze.doors().len(), 4);
+        assert_eq!(maze.most_doors(), 3);
+    }
+
+    #[test]
+    fn test_maze_display() {
+        let expected = ["#####", "#.|.#", "#-###", "#.|X#", "#####", ""].join("\n");
+        assert_eq!(format!("{}", Maze::new(&example_1())), expected);
+
+        let expected = vec![
+            "#########",
+            "#.|.|.|.#",
+            "#-#######",
+            "#.|.|.|.#",
+            "#-#####-#",
+            "#.#.#X|.#",
+            "#-#-#####",
+            "#.|.|.|.#",
+            "#########",
+            "",
+        ];
+        assert_eq!(format!("{}", Maze::new(&example_2())), expected.join("\n"));
+
+        assert_eq!(format!("{}", Maze::new("^$")), "###\n#X#\n###\n");
+    }
+
+    #[test]
+    fn test_maze_export() {
+        let maze = Maze::new(&example_1());
+        assert_eq!(
+            maze.doors(),
+            vec![((-1, 0), (-1, 1)), ((-1, 0), (0, 0)), ((-1, 1), (0, 1))],
+        );
+        assert_eq!(
+            maze.to_dot(),
+            [
+                "graph maze {",
+                "    \"-1,0\";",
+                "    \"-1,1\";",
+                "    \"0,0\";",
+                "    \"0,1\";",
+                "    \"-1,0\" -- \"-1,1\";",
+                "    \"-1,0\" -- \"0,0\";",
+                "    \"-1,1\" -- \"0,1\";",
+                "}",
+                "",
+            ]
+            .join("\n"),
+        );
+        assert_eq!(
+            maze.to_json(),
+            "{\"rooms\": [[-1, 0], [-1, 1], [0, 0], [0, 1]], \
+             \"doors\": [[[-1, 0], [-1, 1]], [[-1, 0], [0, 0]], [[-1, 1], [0, 1]]]}",
+        );
+    }
+
+    #[test]
+    fn test_distances() {
+        let maze = Maze::new(&example_2());
+        let distances = maze.distances();
+
+        assert_eq!(distances.furthest(), ((1, -1), 10));
+        assert_eq!(distances.to((0, 0)), Some(0));
+        assert_eq!(distances.to((1, 0)), Some(1));
+        assert_eq!(distances.to((5, 5)), None);
+        assert_eq!(distances.histogram(), vec![1, 1, 1, 1, 1, 1, 2, 2, 2, 3, 1]);
+        assert_eq!(distances.at_least(9), 4);
+
+        assert_eq!(distances.route_to((1, -1)), Some("^ENWWWSSEEE$".to_string()));
+        assert_eq!(distances.route_to((1, 2)), Some("^ENWWWNEEE$".to_string()));
+        assert_eq!(distances.route_to((0, 0)), Some("^$".to_string()));
+        assert_eq!(distances.route_to((5, 5)), None);
+
+        // Detours never make up part of a shortest route
+        let maze = Maze::new(&example_3());
+        let (furthest, doors) = maze.distances().furthest();
+        let route = maze.distances().route_to(furthest).unwrap();
+        assert_eq!(route.len(), doors + 2);
+        assert_eq!(Maze::new(&route).most_doors(), doors);
+    }
+
+    #[test]
+    fn test_distances_from() {
+        let maze = Maze::new(&example_1());
+        assert!(maze.distances_from((5, 5)).is_none());
+
+        // ^WNE$ is a loop of four rooms walked one way
+        let distances = maze.distances_from((-1, 1)).unwrap();
+        assert_eq!(distances.start, (-1, 1));
+        assert_eq!(distances.to((0, 0)), Some(2));
+        assert_eq!(distances.histogram(), vec![1, 2, 1]);
+        assert_eq!(distances.route_to((0, 0)).as_deref(), Some("^SE$"));
+    }
+
     #[test]
     fn test_part_2() {
         assert_eq!(part_2(&example_1(), 1), 3);
@@ -1,115 +1,257 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::fmt;
+
+use crate::route::{Direction, Route};
 
 pub struct Maze {
     grid: Grid,
+    distances: Distances,
 }
 
 type Grid = BTreeMap<Coordinate, HashSet<Coordinate>>;
 
-type Coordinate = (isize, isize);
+pub type Coordinate = (isize, isize);
 
 impl Maze {
-    pub fn new(pattern: &String) -> Maze {
-        let mut stack: Vec<(isize, isize)> = vec![];
+    /// Builds the facility from a route regex, panicking if it isn't valid
+    pub fn new(pattern: &str) -> Maze {
+        match Route::parse(pattern) {
+            Ok(route) => Maze::from_route(&route),
+            Err(error) => panic!("Invalid route: {}", error),
+        }
+    }
+
+    pub fn from_route(route: &Route) -> Maze {
         let mut grid: Grid = BTreeMap::new();
+        grid.insert((0, 0), Maze::new_room());
 
-        let mut current = (0, 0);
-
-        for c in pattern.chars() {
-            let previous = current.clone();
-
-            match c {
-                '^' => continue,
-                '$' => continue,
-                'N' => current = (current.0, current.1 + 1),
-                'S' => current = (current.0, current.1 - 1),
-                'E' => current = (current.0 + 1, current.1),
-                'W' => current = (current.0 - 1, current.1),
-                '(' => {
-                    stack.push(current);
-                    continue;
-                },
-                '|' => {
-                    current = *stack.last().unwrap();
-                    continue;
-                },
-                ')' => {
-                    current = stack.pop().unwrap();
-                    continue;
-                },
-                _ => panic!(),
-            }
+        let mut starts = BTreeSet::new();
+        starts.insert((0, 0));
+        Maze::walk(&mut grid, route, starts);
+
+        let distances = Distances::new(&grid, (0, 0));
+        Maze { grid, distances }
+    }
 
-            let forward = grid.entry(previous).or_insert_with(|| HashSet::with_capacity(4));
-            forward.insert(current);
+    fn new_room() -> HashSet<Coordinate> {
+        HashSet::with_capacity(4)
+    }
+
+    /// Follows the route from every one of the starting rooms, adding the doors it goes through,
+    /// and returns every room it can end up in
+    fn walk(grid: &mut Grid, route: &Route, starts: BTreeSet<Coordinate>) -> BTreeSet<Coordinate> {
+        match route {
+            Route::Step(direction) => {
+                let (dx, dy) = direction.offset();
+
+                starts
+                    .into_iter()
+                    .map(|previous| {
+                        let current = (previous.0 + dx, previous.1 + dy);
+
+                        let forward = grid.entry(previous).or_insert_with(Maze::new_room);
+                        forward.insert(current);
 
-            let backward = grid.entry(current).or_insert_with(|| HashSet::with_capacity(4));
-            backward.insert(previous);
+                        let backward = grid.entry(current).or_insert_with(Maze::new_room);
+                        backward.insert(previous);
+
+                        current
+                    })
+                    .collect()
+            },
+            Route::Sequence(routes) => {
+                routes.iter().fold(starts, |rooms, route| Maze::walk(grid, route, rooms))
+            },
+            Route::Branch(options) => options
+                .iter()
+                .flat_map(|option| Maze::walk(grid, option, starts.clone()))
+                .collect(),
         }
+    }
 
-        Maze { grid }
+    /// Shortest distances from the room you start in, worked out when the maze was built
+    pub fn distances(&self) -> &Distances {
+        &self.distances
     }
 
+    /// Shortest distances from any room, or None if there's no such room
+    pub fn distances_from(&self, start: Coordinate) -> Option<Distances> {
+        if self.grid.contains_key(&start) {
+            Some(Distances::new(&self.grid, start))
+        } else {
+            None
+        }
+    }
+
+    /// The largest number of doors you'd have to go through to reach a room
     pub fn most_doors(&self) -> usize {
-        let mut visited = HashSet::new();
+        self.distances.furthest().1
+    }
 
-        let mut doors = 0;
-        let mut visit_next = vec![(0, 0)];
+    /// Number of rooms whose shortest path goes through at least n doors
+    pub fn at_least_n_doors_away(&self, n_doors: usize) -> usize {
+        self.distances.at_least(n_doors)
+    }
 
-        while visit_next.len() > 0 {
-            let mut new_visit_next = vec![];
+    /// Every door once, as the pair of rooms it joins with the lower room first, in order
+    pub fn doors(&self) -> Vec<(Coordinate, Coordinate)> {
+        let mut doors = self.grid
+            .iter()
+            .flat_map(|(room, neighbours)| {
+                neighbours.iter().filter(move |n| room < n).map(move |n| (*room, *n))
+            })
+            .collect::<Vec<_>>();
+        doors.sort();
+        doors
+    }
 
-            for current in &visit_next {
-                visited.insert(*current);
+    /// The room graph in Graphviz DOT format, one node per room and one edge per door
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph maze {\n");
+        for (x, y) in self.grid.keys() {
+            dot += &format!("    \"{},{}\";\n", x, y);
+        }
+        for ((x1, y1), (x2, y2)) in self.doors() {
+            dot += &format!("    \"{},{}\" -- \"{},{}\";\n", x1, y1, x2, y2);
+        }
+        dot + "}\n"
+    }
 
-                for next_node in self.grid.get(&current).unwrap() {
-                    if !visited.contains(next_node) {
-                        new_visit_next.push(next_node.clone());
-                    }
-                }
-            }
+    /// The room graph as JSON: `{"rooms": [[x, y], ...], "doors": [[[x, y], [x, y]], ...]}`
+    pub fn to_json(&self) -> String {
+        let rooms = self.grid
+            .keys()
+            .map(|(x, y)| format!("[{}, {}]", x, y))
+            .collect::<Vec<_>>();
+        let doors = self.doors()
+            .iter()
+            .map(|((x1, y1), (x2, y2))| format!("[[{}, {}], [{}, {}]]", x1, y1, x2, y2))
+            .collect::<Vec<_>>();
+        format!("{{\"rooms\": [{}], \"doors\": [{}]}}", rooms.join(", "), doors.join(", "))
+    }
 
-            if new_visit_next.len() > 0 {
-                doors += 1;
+    fn has_door(&self, a: &Coordinate, b: &Coordinate) -> bool {
+        self.grid.get(a).is_some_and(|neighbours| neighbours.contains(b))
+    }
+}
+
+/// How many doors it takes to reach every room from a start room, going the shortest way
+pub struct Distances {
+    pub start: Coordinate,
+    doors: BTreeMap<Coordinate, usize>,
+    /// The room before each room on a shortest route from the start
+    previous: BTreeMap<Coordinate, Coordinate>,
+}
+
+impl Distances {
+    /// Breadth first search out from the start room
+    fn new(grid: &Grid, start: Coordinate) -> Distances {
+        let mut doors = BTreeMap::new();
+        let mut previous = BTreeMap::new();
+        let mut visit_next = VecDeque::new();
+
+        doors.insert(start, 0);
+        visit_next.push_back(start);
+
+        while let Some(current) = visit_next.pop_front() {
+            let distance = doors[&current];
+
+            // Visit neighbours in order so the routes found don't depend on HashSet order
+            let mut neighbours = grid[&current].iter().collect::<Vec<_>>();
+            neighbours.sort();
+
+            for next_room in neighbours {
+                if !doors.contains_key(next_room) {
+                    doors.insert(*next_room, distance + 1);
+                    previous.insert(*next_room, current);
+                    visit_next.push_back(*next_room);
+                }
             }
+        }
 
-            visit_next = new_visit_next;
+        Distances {
+            start,
+            doors,
+            previous,
         }
+    }
 
-        doors
+    /// Number of doors between the start and the room, or None if it can't be reached
+    pub fn to(&self, room: Coordinate) -> Option<usize> {
+        self.doors.get(&room).cloned()
     }
 
-    pub fn at_least_n_doors_away(&self, n_doors: usize) -> usize {
-        let mut visited = HashSet::new();
-        let mut visited_after_n_doors = HashSet::new();
+    /// The room the most doors away, and how many doors that is.  Ties go to the lowest room.
+    pub fn furthest(&self) -> (Coordinate, usize) {
+        self.doors
+            .iter()
+            .fold((self.start, 0), |furthest, (room, doors)| {
+                if *doors > furthest.1 { (*room, *doors) } else { furthest }
+            })
+    }
 
-        let mut doors = 0;
-        let mut visit_next = vec![(0, 0)];
+    /// Number of rooms whose shortest route goes through at least n doors
+    pub fn at_least(&self, n_doors: usize) -> usize {
+        self.doors.values().filter(|doors| **doors >= n_doors).count()
+    }
 
-        while visit_next.len() > 0 {
-            let mut new_visit_next = vec![];
+    /// Number of rooms at each distance, indexed by the number of doors
+    pub fn histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![0; self.furthest().1 + 1];
+        for doors in self.doors.values() {
+            histogram[*doors] += 1;
+        }
+        histogram
+    }
 
-            for current in &visit_next {
-                visited.insert(*current);
+    /// A shortest route from the start to the room as a route regex, e.g. `^NNE$`
+    pub fn route_to(&self, room: Coordinate) -> Option<String> {
+        self.to(room)?;
 
-                for next_node in self.grid.get(&current).unwrap() {
-                    if !visited.contains(next_node) {
-                        new_visit_next.push(next_node.clone());
+        let mut directions = vec![];
+        let mut current = room;
+        while let Some(previous) = self.previous.get(&current) {
+            let offset = (current.0 - previous.0, current.1 - previous.1);
+            directions.push(Direction::from_offset(offset).unwrap().to_char());
+            current = *previous;
+        }
 
-                        if doors + 1 >= n_doors {
-                            visited_after_n_doors.insert(*next_node);
-                        }
-                    }
-                }
-            }
+        Some(format!("^{}$", directions.iter().rev().collect::<String>()))
+    }
+}
+
+/// Draws the facility the way the puzzle does: `X` is where you start, `.` rooms, `|` and `-`
+/// doors, and `#` walls
+impl fmt::Display for Maze {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rooms = self.grid.keys();
+        let min_x = rooms.clone().map(|(x, _y)| *x).min().unwrap();
+        let max_x = rooms.clone().map(|(x, _y)| *x).max().unwrap();
+        let min_y = rooms.clone().map(|(_x, y)| *y).min().unwrap();
+        let max_y = rooms.map(|(_x, y)| *y).max().unwrap();
 
-            if new_visit_next.len() > 0 {
-                doors += 1;
+        let wall = "#".repeat((max_x - min_x + 1) as usize * 2 + 1);
+        writeln!(f, "{}", wall)?;
+
+        // North is up, so start from the highest row
+        for y in (min_y..=max_y).rev() {
+            let mut rooms = String::from("#");
+            let mut below = String::from("#");
+
+            for x in min_x..=max_x {
+                rooms.push(match (x, y) {
+                    (0, 0) => 'X',
+                    room if self.grid.contains_key(&room) => '.',
+                    _ => '#',
+                });
+                rooms.push(if self.has_door(&(x, y), &(x + 1, y)) { '|' } else { '#' });
+                below.push(if self.has_door(&(x, y), &(x, y - 1)) { '-' } else { '#' });
+                below.push('#');
             }
 
-            visit_next = new_visit_next;
+            writeln!(f, "{}", rooms)?;
+            writeln!(f, "{}", if y == min_y { &wall } else { &below })?;
         }
-
-        visited_after_n_doors.len()
+        Ok(())
     }
 }
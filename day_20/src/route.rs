@@ -0,0 +1,222 @@
+use std::fmt;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'N' => Some(Direction::North),
+            'S' => Some(Direction::South),
+            'E' => Some(Direction::East),
+            'W' => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Direction::North => 'N',
+            Direction::South => 'S',
+            Direction::East => 'E',
+            Direction::West => 'W',
+        }
+    }
+
+    pub fn from_offset(offset: (isize, isize)) -> Option<Direction> {
+        match offset {
+            (0, 1) => Some(Direction::North),
+            (0, -1) => Some(Direction::South),
+            (1, 0) => Some(Direction::East),
+            (-1, 0) => Some(Direction::West),
+            _ => None,
+        }
+    }
+
+    /// The (x, y) step through a door in this direction, with north as +y
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, 1),
+            Direction::South => (0, -1),
+            Direction::East => (1, 0),
+            Direction::West => (-1, 0),
+        }
+    }
+}
+
+/// A route regex like `^ENWWW(NEEE|SSE(EE|N))$`, as a tree
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum Route {
+    Step(Direction),
+    /// Routes followed one after the other.  An empty sequence goes nowhere, like the empty
+    /// option in `(NEWS|)`.
+    Sequence(Vec<Route>),
+    /// Any one of the routes, e.g. `(N|S)`
+    Branch(Vec<Route>),
+}
+
+/// A problem with a route regex found by Route#parse, at a char position in the regex
+#[derive(Eq, PartialEq, Debug)]
+pub enum RouteError {
+    MissingStart,
+    MissingEnd,
+    UnexpectedChar { position: usize, c: char },
+    UnclosedBranch { position: usize },
+    UnmatchedClose { position: usize },
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouteError::MissingStart => write!(f, "route doesn't start with '^'"),
+            RouteError::MissingEnd => write!(f, "route doesn't end with '$'"),
+            RouteError::UnexpectedChar { position, c } => {
+                write!(f, "{}: unexpected '{}'", position, c)
+            },
+            RouteError::UnclosedBranch { position } => {
+                write!(f, "{}: '(' is never closed", position)
+            },
+            RouteError::UnmatchedClose { position } => {
+                write!(f, "{}: ')' without a '('", position)
+            },
+        }
+    }
+}
+
+impl Route {
+    pub fn parse(pattern: &str) -> Result<Route, RouteError> {
+        let chars = pattern.chars().collect::<Vec<_>>();
+
+        if chars.first() != Some(&'^') {
+            return Err(RouteError::MissingStart);
+        }
+
+        let mut position = 1;
+        let route = Route::parse_branch(&chars, &mut position)?;
+
+        match (chars.get(position), chars.get(position + 1)) {
+            (Some('$'), None) => Ok(route),
+            (Some('$'), Some(c)) => {
+                Err(RouteError::UnexpectedChar { position: position + 1, c: *c })
+            },
+            (Some(')'), _) => Err(RouteError::UnmatchedClose { position }),
+            _ => Err(RouteError::MissingEnd),
+        }
+    }
+
+    /// Parses options separated by `|` up to a `)`, `$` or the end, leaving `position` there.
+    /// A lone option isn't wrapped in a Branch.
+    fn parse_branch(chars: &[char], position: &mut usize) -> Result<Route, RouteError> {
+        let mut options = vec![Route::parse_sequence(chars, position)?];
+
+        while chars.get(*position) == Some(&'|') {
+            *position += 1;
+            options.push(Route::parse_sequence(chars, position)?);
+        }
+
+        if options.len() == 1 {
+            Ok(options.pop().unwrap())
+        } else {
+            Ok(Route::Branch(options))
+        }
+    }
+
+    /// Parses directions and bracketed branches up to a `|`, `)`, `$` or the end
+    fn parse_sequence(chars: &[char], position: &mut usize) -> Result<Route, RouteError> {
+        let mut routes = vec![];
+
+        while let Some(c) = chars.get(*position) {
+            match c {
+                '|' | ')' | '$' => break,
+                '(' => {
+                    let open = *position;
+                    *position += 1;
+                    routes.push(Route::parse_branch(chars, position)?);
+                    if chars.get(*position) != Some(&')') {
+                        return Err(RouteError::UnclosedBranch { position: open });
+                    }
+                },
+                c => match Direction::from_char(*c) {
+                    Some(direction) => routes.push(Route::Step(direction)),
+                    None => return Err(RouteError::UnexpectedChar { position: *position, c: *c }),
+                },
+            }
+            *position += 1;
+        }
+
+        Ok(Route::Sequence(routes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Direction::*;
+
+    fn steps(directions: &[Direction]) -> Route {
+        Route::Sequence(directions.iter().map(|d| Route::Step(*d)).collect())
+    }
+
+    #[test]
+    fn test_route_parse() {
+        assert_eq!(Route::parse("^WNE$"), Ok(steps(&[West, North, East])));
+        assert_eq!(Route::parse("^$"), Ok(steps(&[])));
+
+        assert_eq!(
+            Route::parse("^N(E|W(S|))$"),
+            Ok(Route::Sequence(vec![
+                Route::Step(North),
+                Route::Branch(vec![
+                    steps(&[East]),
+                    Route::Sequence(vec![
+                        Route::Step(West),
+                        Route::Branch(vec![steps(&[South]), steps(&[])]),
+                    ]),
+                ]),
+            ])),
+        );
+
+        // An empty option
+        assert_eq!(
+            Route::parse("^(NEWS|)S$"),
+            Ok(Route::Sequence(vec![
+                Route::Branch(vec![steps(&[North, East, West, South]), steps(&[])]),
+                Route::Step(South),
+            ])),
+        );
+
+        // The whole route can branch too
+        assert_eq!(
+            Route::parse("^N|S$"),
+            Ok(Route::Branch(vec![steps(&[North]), steps(&[South])])),
+        );
+
+        // Brackets with a single option are just a sequence
+        assert_eq!(
+            Route::parse("^(NE)$"),
+            Ok(Route::Sequence(vec![steps(&[North, East])])),
+        );
+    }
+
+    #[test]
+    fn test_route_parse_errors() {
+        assert_eq!(Route::parse("WNE$"), Err(RouteError::MissingStart));
+        assert_eq!(Route::parse("^WNE"), Err(RouteError::MissingEnd));
+        assert_eq!(
+            Route::parse("^WXE$"),
+            Err(RouteError::UnexpectedChar { position: 2, c: 'X' }),
+        );
+        assert_eq!(Route::parse("^W(N|S$"), Err(RouteError::UnclosedBranch { position: 2 }));
+        assert_eq!(Route::parse("^WN)S$"), Err(RouteError::UnmatchedClose { position: 3 }));
+        assert_eq!(Route::parse("^N$S"), Err(RouteError::UnexpectedChar { position: 3, c: 'S' }));
+        assert_eq!(
+            format!("{}", RouteError::UnclosedBranch { position: 2 }),
+            "2: '(' is never closed",
+        );
+    }
+}
@@ -1,33 +1,62 @@
 use std::collections::HashMap;
+use std::fmt;
+
 use crate::cave::Gear::{Torch, Climbing, Nothing};
 
+/// The cave stretches on forever, so its regions are only worked out as they're asked for.
+///
+/// A region's erosion level depends on the regions to its left and above it, so working out one
+/// region means working out the whole rectangle between it and the mouth of the cave.  Erosion
+/// levels are kept row by row, each row at least as long as the rows below it, and rows only ever
+/// grow: the cave remembers exactly the area that's been asked about and nothing more.
 pub struct Cave {
     pub depth: usize,
     pub target_x: usize,
     pub target_y: usize,
-    pub regions: HashMap<Coordinate, Region>,
+    erosion_levels: Vec<Vec<usize>>,
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
+#[derive(Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Copy, Debug)]
 pub struct Coordinate {
     pub x: usize,
     pub y: usize,
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
+#[derive(Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Copy, Debug)]
 pub enum Gear {
     Torch,
     Climbing,
     Nothing,
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
+impl Gear {
+    /// How a route shows the gear it's holding when drawn over the cave
+    pub fn to_char(self) -> char {
+        match self {
+            Torch => 't',
+            Climbing => 'c',
+            Nothing => 'n',
+        }
+    }
+}
+
+impl fmt::Display for Gear {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Torch => write!(f, "torch"),
+            Climbing => write!(f, "climbing gear"),
+            Nothing => write!(f, "neither"),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Hash, Ord, PartialOrd, Clone, Copy, Debug)]
 pub struct CoordGear {
     pub coordinate: Coordinate,
     pub gear: Gear,
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
 pub struct Region {
     pub erosion_level: usize,
     pub region_risk: usize,
@@ -60,56 +89,104 @@ impl Region {
     }
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
 pub enum RegionType {
     Rocky,
     Wet,
     Narrow,
 }
 
+impl RegionType {
+    pub fn to_char(self) -> char {
+        match self {
+            RegionType::Rocky => '.',
+            RegionType::Wet => '=',
+            RegionType::Narrow => '|',
+        }
+    }
+}
+
 impl Cave {
     pub fn new(target: &Coordinate, depth: usize) -> Cave {
         Cave {
-            depth: depth,
+            depth,
             target_x: target.x,
             target_y: target.y,
-            regions: HashMap::new(),
+            erosion_levels: vec![],
         }
     }
 
-    pub fn compute_to(&mut self, to_x: usize, to_y: usize) {
-        for y in 0..=to_y {
-            for x in 0..=to_x {
-                let coord = Coordinate { x, y };
-                if self.regions.contains_key(&coord) {
-                    continue;
-                }
+    pub fn region(&mut self, coordinate: &Coordinate) -> Region {
+        Region::new(self.erosion_level(coordinate))
+    }
+
+    pub fn erosion_level(&mut self, coordinate: &Coordinate) -> usize {
+        let Coordinate { x, y } = *coordinate;
+
+        if let Some(&erosion_level) = self.erosion_levels.get(y).and_then(|row| row.get(x)) {
+            return erosion_level;
+        }
 
-                let geologic_index = self.geologic_index(&coord);
-                let erosion_level = self.erosion_level(geologic_index);
-                let region = Region::new(erosion_level);
+        // Fill in every row down to this one out to this column, top to bottom and left to
+        // right, so the regions left of and above each new one are always known already
+        for row in 0..=y {
+            if row == self.erosion_levels.len() {
+                self.erosion_levels.push(vec![]);
+            }
 
-                self.regions.insert(coord, region);
+            for column in self.erosion_levels[row].len()..=x {
+                let geologic_index = self.geologic_index(&Coordinate { x: column, y: row });
+                self.erosion_levels[row].push((geologic_index + self.depth) % 20183);
             }
         }
+
+        self.erosion_levels[y][x]
     }
 
-    pub fn erosion_level(&self, geologic_index: usize) -> usize {
-        (geologic_index + self.depth) % 20183
+    /// Draws the regions from top_left to bottom_right inclusive as in the puzzle: `.` for rocky,
+    /// `=` for wet, `|` for narrow, `M` for the mouth and `T` for the target.  With an overlay,
+    /// e.g. a route's path, every region in it is marked with the gear held there instead (see
+    /// Gear#to_char).
+    pub fn render(
+        &mut self,
+        top_left: &Coordinate,
+        bottom_right: &Coordinate,
+        overlay: Option<&[CoordGear]>,
+    ) -> String {
+        let gear_held: HashMap<Coordinate, Gear> = overlay
+            .map(|overlay| overlay.iter().map(|cg| (cg.coordinate, cg.gear)).collect())
+            .unwrap_or_default();
+
+        let mut output = String::new();
+        for y in top_left.y..=bottom_right.y {
+            for x in top_left.x..=bottom_right.x {
+                let coordinate = Coordinate { x, y };
+                output.push(match coordinate {
+                    Coordinate { x: 0, y: 0 } => 'M',
+                    Coordinate { x, y } if x == self.target_x && y == self.target_y => 'T',
+                    _ => match gear_held.get(&coordinate) {
+                        Some(gear) => gear.to_char(),
+                        None => self.region(&coordinate).region_type.to_char(),
+                    },
+                });
+            }
+            output.push('\n');
+        }
+        output
     }
 
-    pub fn geologic_index(&self, coordinate: &Coordinate) -> usize {
-        let Cave { target_x: tx, target_y: ty, .. } = self;
+    pub fn geologic_index(&mut self, coordinate: &Coordinate) -> usize {
+        let (tx, ty) = (self.target_x, self.target_y);
 
-        match coordinate {
+        match *coordinate {
             Coordinate { x: 0, y: 0 } => 0,
             Coordinate { x, y } if (x == tx && y == ty) => 0,
             Coordinate { x, y: 0 } => x * 16807,
             Coordinate { x: 0, y } => y * 48271,
             Coordinate { x, y } => {
-                let coord_left = self.regions.get(&Coordinate { x: x - 1, y: *y }).unwrap();
-                let coord_up = self.regions.get(&Coordinate { x: *x, y: y - 1 }).unwrap();
-                coord_left.erosion_level * coord_up.erosion_level
+                let erosion_left = self.erosion_level(&Coordinate { x: x - 1, y });
+                let erosion_up = self.erosion_level(&Coordinate { x, y: y - 1 });
+                erosion_left * erosion_up
             },
         }
     }
@@ -123,7 +200,6 @@ mod tests {
     #[test]
     fn test_cave() {
         let mut cave = example_1();
-        cave.compute_to(cave.target_x, cave.target_y);
 
         assert_eq!(cave.geologic_index(&Coordinate { x: 0, y: 0 }), 0);
         assert_eq!(cave.geologic_index(&Coordinate { x: 1, y: 0 }), 16807);
@@ -131,10 +207,81 @@ mod tests {
         assert_eq!(cave.geologic_index(&Coordinate { x: 1, y: 1 }), 145722555);
         assert_eq!(cave.geologic_index(&Coordinate { x: 10, y: 10 }), 0);
 
-        assert_eq!(*cave.regions.get(&Coordinate { x: 0, y: 0 }).unwrap(), Region { erosion_level: 510, region_type: RegionType::Rocky, region_risk: 0 });
-        assert_eq!(*cave.regions.get(&Coordinate { x: 1, y: 0 }).unwrap(), Region { erosion_level: 17317, region_type: RegionType::Wet, region_risk: 1 });
-        assert_eq!(*cave.regions.get(&Coordinate { x: 0, y: 1 }).unwrap(), Region { erosion_level: 8415, region_type: RegionType::Rocky, region_risk: 0 });
-        assert_eq!(*cave.regions.get(&Coordinate { x: 1, y: 1 }).unwrap(), Region { erosion_level: 1805, region_type: RegionType::Narrow, region_risk: 2 });
-        assert_eq!(*cave.regions.get(&Coordinate { x: 10, y: 10 }).unwrap(), Region { erosion_level: 510, region_type: RegionType::Rocky, region_risk: 0 });
+        assert_eq!(cave.region(&Coordinate { x: 0, y: 0 }), Region { erosion_level: 510, region_type: RegionType::Rocky, region_risk: 0 });
+        assert_eq!(cave.region(&Coordinate { x: 1, y: 0 }), Region { erosion_level: 17317, region_type: RegionType::Wet, region_risk: 1 });
+        assert_eq!(cave.region(&Coordinate { x: 0, y: 1 }), Region { erosion_level: 8415, region_type: RegionType::Rocky, region_risk: 0 });
+        assert_eq!(cave.region(&Coordinate { x: 1, y: 1 }), Region { erosion_level: 1805, region_type: RegionType::Narrow, region_risk: 2 });
+        assert_eq!(cave.region(&Coordinate { x: 10, y: 10 }), Region { erosion_level: 510, region_type: RegionType::Rocky, region_risk: 0 });
+    }
+
+    /// How many regions the cave has worked out so far
+    fn known_regions(cave: &Cave) -> usize {
+        cave.erosion_levels.iter().map(|row| row.len()).sum()
+    }
+
+    #[test]
+    fn test_cave_lazy() {
+        let mut cave = example_1();
+        assert_eq!(known_regions(&cave), 0);
+
+        // Asking about one region works out everything between it and the mouth, once
+        assert_eq!(cave.region(&Coordinate { x: 2, y: 1 }).region_type, RegionType::Wet);
+        assert_eq!(known_regions(&cave), 6);
+        assert_eq!(cave.region(&Coordinate { x: 0, y: 1 }).region_type, RegionType::Rocky);
+        assert_eq!(known_regions(&cave), 6);
+
+        // Going deeper doesn't need the regions further right
+        assert_eq!(cave.region(&Coordinate { x: 0, y: 4 }).region_type, RegionType::Wet);
+        assert_eq!(known_regions(&cave), 9);
+
+        // Out beyond the target, and far enough that working it out recursively would overflow
+        cave.region(&Coordinate { x: 30, y: 5000 });
+        assert_eq!(known_regions(&cave), 31 * 5001);
+    }
+
+    #[test]
+    fn test_cave_render() {
+        let mut cave = example_1();
+
+        // The puzzle's picture of the example
+        let expected = vec![
+            "M=.|=.|.|=.|=|=.",
+            ".|=|=|||..|.=...",
+            ".==|....||=..|==",
+            "=.|....|.==.|==.",
+            "=|..==...=.|==..",
+            "=||.=.=||=|=..|=",
+            "|.=.===|||..=..|",
+            "|..==||=.|==|===",
+            ".=..===..=|.|||.",
+            ".======|||=|=.|=",
+            ".===|=|===T===||",
+            "=|||...|==..|=.|",
+            "=.=|=.=..=.||==|",
+            "||=|=...|==.=|==",
+            "|=.=||===.|||===",
+            "||.|==.|.|.||=||",
+        ];
+        let rendered = cave.render(&Coordinate { x: 0, y: 0 }, &Coordinate { x: 15, y: 15 }, None);
+        assert_eq!(rendered, expected.join("\n") + "\n");
+
+        // Any window
+        let rendered = cave.render(&Coordinate { x: 9, y: 9 }, &Coordinate { x: 11, y: 11 }, None);
+        assert_eq!(rendered, "|=|\n=T=\n=..\n");
+    }
+
+    #[test]
+    fn test_cave_render_overlay() {
+        let mut cave = example_1();
+        let overlay = [
+            CoordGear { coordinate: Coordinate { x: 0, y: 0 }, gear: Torch },
+            CoordGear { coordinate: Coordinate { x: 0, y: 1 }, gear: Torch },
+            CoordGear { coordinate: Coordinate { x: 1, y: 1 }, gear: Nothing },
+        ];
+        let (top_left, bottom_right) = (Coordinate { x: 0, y: 0 }, Coordinate { x: 2, y: 1 });
+
+        // The mouth still shows, and the other regions keep their type
+        assert_eq!(cave.render(&top_left, &bottom_right, Some(&overlay)), "M=.\ntn=\n");
+        assert_eq!(cave.render(&top_left, &bottom_right, None), "M=.\n.|=\n");
     }
 }
@@ -1,139 +1,63 @@
 mod cave;
+mod rescue;
 
 use crate::cave::*;
-use std::collections::HashMap;
+use crate::rescue::rescue;
+use std::env;
 
 fn main() {
     let target = Coordinate { x: 9, y: 796 };
     let depth = 6969;
 
+    // `cargo run -- route` shows the fastest way to the target, minute by minute and on a map
+    match env::args().nth(1).as_deref() {
+        Some("route") => {
+            let mut cave = Cave::new(&target, depth);
+            let route = rescue(&mut cave);
+            return print!("{}\n{}", route, route.render(&mut cave));
+        },
+        Some("render") => return render(Cave::new(&target, depth), env::args().skip(2).collect()),
+        _ => (),
+    }
+
     println!("part_1: {}", part_1(Cave::new(&target, depth)));
     println!("part_2: {}", part_2(Cave::new(&target, depth)));
 }
 
 fn part_1(mut cave: Cave) -> usize {
-    cave.compute_to(cave.target_x, cave.target_y);
-    cave.regions
-        .values()
-        .map(|region| region.region_risk)
-        .sum()
+    let mut risk = 0;
+    for y in 0..=cave.target_y {
+        for x in 0..=cave.target_x {
+            risk += cave.region(&Coordinate { x, y }).region_risk;
+        }
+    }
+    risk
 }
 
 fn part_2(mut cave: Cave) -> usize {
-    use Gear::*;
-
-    cave.compute_to(cave.target_x, cave.target_y);
-
-    // Places we've been, with lowest cost
-    let mut visited: HashMap<CoordGear, usize> = HashMap::new();
+    rescue(&mut cave).minutes
+}
 
-    // Final answer
-    let mut done = false;
-    let target = CoordGear {
-        coordinate: Coordinate { x: cave.target_x, y: cave.target_y },
-        gear: Gear::Torch,
+// `cargo run -- render [x1 y1 x2 y2] [route]` draws the cave between the two corners, or from the
+// mouth to a little past the target, optionally with the fastest route to the target over it
+fn render(mut cave: Cave, mut args: Vec<String>) {
+    let path = if args.last().map(|arg| arg.as_str()) == Some("route") {
+        args.pop();
+        Some(rescue(&mut cave).path())
+    } else {
+        None
     };
 
-    let start = CoordGear {
-        coordinate: Coordinate { x: 0, y: 0 },
-        gear: Torch,
+    let corners = args.iter().map(|arg| arg.parse().unwrap()).collect::<Vec<usize>>();
+    let (top_left, bottom_right) = match corners.as_slice() {
+        [x1, y1, x2, y2] => (Coordinate { x: *x1, y: *y1 }, Coordinate { x: *x2, y: *y2 }),
+        _ => (
+            Coordinate { x: 0, y: 0 },
+            Coordinate { x: cave.target_x + 5, y: cave.target_y + 5 },
+        ),
     };
-    visited.insert(start, 0);
-
-    // Current edge of exploration, all lowest cost
-    let mut current_nodes = vec![
-        start,
-    ];
-
-    while !done {
-        let mut visit_next: Vec<CoordGear> = vec![];
-
-        for coord_gear in &current_nodes {
-            let current_region = cave.regions.get(&coord_gear.coordinate).unwrap();
-            let current_cost = visited.get(&coord_gear).unwrap().to_owned();
-
-            // Valid switching
-            for gear in &current_region.valid_gear() {
-                let new_cost = current_cost + 7;
-                let new_coord_gear = CoordGear {
-                    coordinate: coord_gear.coordinate,
-                    gear: *gear,
-                };
-
-                visited
-                    .entry(new_coord_gear)
-                    .and_modify(|c| {
-                        if new_cost < *c {
-                            visit_next.push(new_coord_gear);
-                            *c = new_cost;
-                        }
-                    })
-                    .or_insert_with(|| {
-                        visit_next.push(new_coord_gear);
-                        new_cost
-                    });
-            }
-
-            for change in &[(1, 0), (-1, 0), (0, 1), (0, -1)] {
-                let (dx, dy) = change;
-
-                if *dx < 0 && coord_gear.coordinate.x == 0 {
-                    continue;
-                }
-
-                if *dy < 0 && coord_gear.coordinate.y == 0 {
-                    continue;
-                }
-
-                let adjacent_coord = Coordinate {
-                    x: (coord_gear.coordinate.x as isize + dx) as usize,
-                    y: (coord_gear.coordinate.y as isize + dy) as usize,
-                };
-                let adjacent_coord_gear = CoordGear {
-                    coordinate: adjacent_coord,
-                    gear: coord_gear.gear,
-                };
-
-                // Expand computed cave if needed
-                if !cave.regions.contains_key(&adjacent_coord) {
-                    cave.compute_to(adjacent_coord.x + 100, adjacent_coord.y + 100);
-                }
-
-                let adjacent_region = cave.regions.get(&adjacent_coord).unwrap();
-
-                // Valid moving
-                if adjacent_region.valid_gear().contains(&coord_gear.gear) {
-                    let new_cost = current_cost + 1;
-
-                    visited
-                        .entry(adjacent_coord_gear)
-                        .and_modify(|c| {
-                            if new_cost < *c {
-                                visit_next.push(adjacent_coord_gear);
-                                *c = new_cost;
-                            }
-                        })
-                        .or_insert_with(|| {
-                            visit_next.push(adjacent_coord_gear);
-                            new_cost
-                        });
-                }
-            }
-        }
-
-        // Check whether we're done yet: we've reached the target AND all of the next places to
-        // visit already exceed the cost of reaching the target.
-        done = if let Some(current_lowest_cost) = visited.get(&target) {
-            visit_next.retain(|cg| visited.get(cg).unwrap() <= current_lowest_cost);
-            visit_next.len() == 0
-        } else {
-            false
-        };
-
-        current_nodes = visit_next;
-    }
 
-    *visited.get(&target).unwrap()
+    print!("{}", cave.render(&top_left, &bottom_right, path.as_deref()));
 }
 
 #[cfg(test)]
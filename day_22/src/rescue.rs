@@ -0,0 +1,242 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+use crate::cave::{Cave, CoordGear, Coordinate, Gear};
+
+/// It takes one minute to move into an adjacent region, and seven to change gear
+const MOVE_MINUTES: usize = 1;
+const SWITCH_MINUTES: usize = 7;
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Action {
+    Move(Coordinate),
+    Switch(Gear),
+}
+
+/// One thing done on the way to the target, and the minute it's finished by
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Step {
+    pub minute: usize,
+    pub action: Action,
+}
+
+/// The fastest way to the target, starting at the mouth of the cave with the torch equipped
+#[derive(Eq, PartialEq, Debug)]
+pub struct Route {
+    pub steps: Vec<Step>,
+    pub minutes: usize,
+}
+
+impl Route {
+    /// Every region the route passes through, in order, with the gear held on arriving there
+    pub fn path(&self) -> Vec<CoordGear> {
+        let mut coord_gear = start();
+        let mut path = vec![coord_gear];
+
+        for step in &self.steps {
+            match step.action {
+                Action::Move(coordinate) => {
+                    coord_gear.coordinate = coordinate;
+                    path.push(coord_gear);
+                },
+                Action::Switch(gear) => coord_gear.gear = gear,
+            }
+        }
+        path
+    }
+
+    /// The part of the cave the route passes through, with the route drawn over it
+    pub fn render(&self, cave: &mut Cave) -> String {
+        let path = self.path();
+        let bottom_right = Coordinate {
+            x: path.iter().map(|cg| cg.coordinate.x).max().unwrap(),
+            y: path.iter().map(|cg| cg.coordinate.y).max().unwrap(),
+        };
+
+        cave.render(&Coordinate { x: 0, y: 0 }, &bottom_right, Some(&path))
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            match step.action {
+                Action::Move(Coordinate { x, y }) => {
+                    writeln!(f, "{: >5}: move to {},{}", step.minute, x, y)?
+                },
+                Action::Switch(gear) => writeln!(f, "{: >5}: switch to {}", step.minute, gear)?,
+            }
+        }
+        writeln!(f, "{} minutes", self.minutes)
+    }
+}
+
+fn start() -> CoordGear {
+    CoordGear {
+        coordinate: Coordinate { x: 0, y: 0 },
+        gear: Gear::Torch,
+    }
+}
+
+/// Finds the fastest route to the target with A*.  It takes at least a minute per region to get
+/// there, plus a gear change if the torch isn't equipped, so that's the estimate of what's left.
+/// Regions of the cave are only worked out as the search reaches them.
+pub fn rescue(cave: &mut Cave) -> Route {
+    let target = CoordGear {
+        coordinate: Coordinate { x: cave.target_x, y: cave.target_y },
+        gear: Gear::Torch,
+    };
+    let estimate = |coord_gear: &CoordGear| {
+        let Coordinate { x, y } = coord_gear.coordinate;
+        let switch = if coord_gear.gear == target.gear { 0 } else { SWITCH_MINUTES };
+        x.abs_diff(target.coordinate.x) + y.abs_diff(target.coordinate.y) + switch
+    };
+
+    // Fastest known time to each place, and where it was reached from
+    let mut minutes: HashMap<CoordGear, usize> = HashMap::new();
+    let mut previous: HashMap<CoordGear, CoordGear> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+
+    minutes.insert(start(), 0);
+    queue.push(Reverse((estimate(&start()), 0, start())));
+
+    while let Some(Reverse((_estimate, current_minutes, coord_gear))) = queue.pop() {
+        if coord_gear == target {
+            break;
+        }
+        // Already reached faster some other way
+        if current_minutes > minutes[&coord_gear] {
+            continue;
+        }
+
+        for (next, cost) in neighbours(cave, &coord_gear) {
+            let next_minutes = current_minutes + cost;
+            if next_minutes < *minutes.get(&next).unwrap_or(&usize::MAX) {
+                minutes.insert(next, next_minutes);
+                previous.insert(next, coord_gear);
+                queue.push(Reverse((next_minutes + estimate(&next), next_minutes, next)));
+            }
+        }
+    }
+
+    // Walk back from the target to build up the steps
+    let mut steps = vec![];
+    let mut coord_gear = target;
+    while let Some(&from) = previous.get(&coord_gear) {
+        let action = if from.coordinate == coord_gear.coordinate {
+            Action::Switch(coord_gear.gear)
+        } else {
+            Action::Move(coord_gear.coordinate)
+        };
+        steps.push(Step { minute: minutes[&coord_gear], action });
+        coord_gear = from;
+    }
+    steps.reverse();
+
+    Route { steps, minutes: minutes[&target] }
+}
+
+/// Everywhere that can be reached in one step, and how long it takes: switching to the other
+/// gear this region allows, or moving to an adjacent region that allows the current gear
+fn neighbours(cave: &mut Cave, coord_gear: &CoordGear) -> Vec<(CoordGear, usize)> {
+    let CoordGear { coordinate, gear } = *coord_gear;
+    let mut neighbours = vec![];
+
+    for &other_gear in &cave.region(&coordinate).valid_gear() {
+        if other_gear != gear {
+            neighbours.push((CoordGear { coordinate, gear: other_gear }, SWITCH_MINUTES));
+        }
+    }
+
+    for (dx, dy) in &[(1, 0), (-1, 0), (0, 1), (0, -1)] {
+        let x = coordinate.x as isize + dx;
+        let y = coordinate.y as isize + dy;
+        if x < 0 || y < 0 {
+            continue;
+        }
+
+        let adjacent = Coordinate { x: x as usize, y: y as usize };
+        if cave.region(&adjacent).valid_gear().contains(&gear) {
+            neighbours.push((CoordGear { coordinate: adjacent, gear }, MOVE_MINUTES));
+        }
+    }
+
+    neighbours
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::example_1;
+
+    #[test]
+    fn test_rescue() {
+        let mut cave = example_1();
+        let route = rescue(&mut cave);
+        assert_eq!(route.minutes, 45);
+
+        // Every minute is accounted for by a move or a switch, ending with the torch at the target
+        let switches = route
+            .steps
+            .iter()
+            .filter(|step| matches!(step.action, Action::Switch(_)))
+            .count();
+        let moves = route.steps.len() - switches;
+        assert_eq!(moves * MOVE_MINUTES + switches * SWITCH_MINUTES, route.minutes);
+
+        assert_eq!(route.steps.last().unwrap().action, Action::Switch(Gear::Torch));
+        assert_eq!(route.steps.last().unwrap().minute, 45);
+        assert!(route.steps.windows(2).all(|steps| steps[0].minute < steps[1].minute));
+
+        let path = route.path();
+        assert_eq!(path.first(), Some(&start()));
+        assert_eq!(path.last().unwrap().coordinate, Coordinate { x: 10, y: 10 });
+        assert_eq!(path.len(), moves + 1);
+        assert!(path.iter().all(|cg| cave.region(&cg.coordinate).valid_gear().contains(&cg.gear)));
+    }
+
+    #[test]
+    fn test_route_display() {
+        let route = Route {
+            steps: vec![
+                Step { minute: 1, action: Action::Move(Coordinate { x: 0, y: 1 }) },
+                Step { minute: 8, action: Action::Switch(Gear::Climbing) },
+            ],
+            minutes: 8,
+        };
+        assert_eq!(
+            route.to_string(),
+            "    1: move to 0,1\n    8: switch to climbing gear\n8 minutes\n",
+        );
+    }
+
+    #[test]
+    fn test_route_render() {
+        let mut cave = example_1();
+        let route = Route {
+            steps: vec![
+                Step { minute: 1, action: Action::Move(Coordinate { x: 0, y: 1 }) },
+                Step { minute: 2, action: Action::Move(Coordinate { x: 1, y: 1 }) },
+                Step { minute: 9, action: Action::Switch(Gear::Nothing) },
+                Step { minute: 10, action: Action::Move(Coordinate { x: 2, y: 1 }) },
+            ],
+            minutes: 10,
+        };
+        assert_eq!(route.render(&mut cave), "M=.\nttn\n");
+    }
+
+    #[test]
+    fn test_route_render_over_cave() {
+        let mut cave = example_1();
+        let route = rescue(&mut cave);
+        let (top_left, bottom_right) = (Coordinate { x: 0, y: 0 }, Coordinate { x: 15, y: 15 });
+        let rendered = cave.render(&top_left, &bottom_right, Some(&route.path()));
+
+        // The mouth and target still show, and every other region on the route is marked
+        assert!(rendered.starts_with('M'));
+        assert_eq!(rendered.lines().nth(10).unwrap().chars().nth(10), Some('T'));
+        let marked = rendered.chars().filter(|c| "tcn".contains(*c)).count();
+        assert_eq!(marked, route.path().len() - 2);
+    }
+}
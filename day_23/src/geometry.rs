@@ -0,0 +1,231 @@
+use std::collections::BTreeSet;
+
+use crate::nanobot::Nanobot;
+
+#[derive(Eq, PartialEq, Hash, Ord, PartialOrd, Copy, Clone, Debug)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+    pub z: isize,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0, z: 0 };
+
+    /// Manhattan distance between the points
+    pub fn distance(&self, other: &Point) -> isize {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+}
+
+/// Every point within a Manhattan distance of the centre, e.g. everything a nanobot can reach
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Octahedron {
+    pub centre: Point,
+    pub radius: isize,
+}
+
+impl Octahedron {
+    pub fn contains(&self, point: &Point) -> bool {
+        self.centre.distance(point) <= self.radius
+    }
+
+    /// Whether any point is in both.  Walking from one centre towards the other a step at a time
+    /// passes through a shared point whenever the radii cover the distance between them.
+    pub fn intersects(&self, other: &Octahedron) -> bool {
+        self.centre.distance(&other.centre) <= self.radius + other.radius
+    }
+
+    /// Manhattan distance from the nearest point in the octahedron to the nearest point in the
+    /// cube, or 0 if they overlap
+    pub fn distance_to_cube(&self, cube: &Cube) -> isize {
+        (cube.distance_to(&self.centre) - self.radius).max(0)
+    }
+}
+
+/// A cube of points from (x, y, z) to (x + size - 1, y + size - 1, z + size - 1)
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug)]
+pub struct Cube {
+    pub corner: Point,
+    pub size: isize,
+}
+
+impl Cube {
+    /// The smallest cube with a power-of-two size holding every point in range of any nanobot
+    pub fn bounding(nanobots: &[Nanobot]) -> Cube {
+        let min = |axis: fn(&Nanobot) -> isize| {
+            nanobots.iter().map(|nb| axis(nb) - nb.r).min().unwrap()
+        };
+        let max = |axis: fn(&Nanobot) -> isize| {
+            nanobots.iter().map(|nb| axis(nb) + nb.r).max().unwrap()
+        };
+
+        let corner = Point { x: min(|nb| nb.x), y: min(|nb| nb.y), z: min(|nb| nb.z) };
+        let extent = (max(|nb| nb.x) - corner.x)
+            .max(max(|nb| nb.y) - corner.y)
+            .max(max(|nb| nb.z) - corner.z)
+            + 1;
+
+        let mut size = 1;
+        while size < extent {
+            size *= 2;
+        }
+
+        Cube { corner, size }
+    }
+
+    /// Manhattan distance from the point to the nearest point in the cube
+    pub fn distance_to(&self, point: &Point) -> isize {
+        let axis = |v: isize, min: isize| {
+            let max = min + self.size - 1;
+            if v < min {
+                min - v
+            } else if v > max {
+                v - max
+            } else {
+                0
+            }
+        };
+        axis(point.x, self.corner.x) + axis(point.y, self.corner.y) + axis(point.z, self.corner.z)
+    }
+
+    /// The eight cubes of half the size that fill this one
+    pub fn split(&self) -> Vec<Cube> {
+        let size = self.size / 2;
+        let mut cubes = Vec::with_capacity(8);
+        for &dx in &[0, size] {
+            for &dy in &[0, size] {
+                for &dz in &[0, size] {
+                    let corner = Point {
+                        x: self.corner.x + dx,
+                        y: self.corner.y + dy,
+                        z: self.corner.z + dz,
+                    };
+                    cubes.push(Cube { corner, size });
+                }
+            }
+        }
+        cubes
+    }
+}
+
+/// Which nanobots' ranges overlap which others, by index into the nanobots it was built from
+#[derive(Debug)]
+pub struct OverlapGraph {
+    neighbours: Vec<BTreeSet<usize>>,
+}
+
+impl OverlapGraph {
+    pub fn new(nanobots: &[Nanobot]) -> OverlapGraph {
+        let ranges = nanobots.iter().map(|nb| nb.range()).collect::<Vec<_>>();
+        let mut neighbours = vec![BTreeSet::new(); ranges.len()];
+
+        for i in 0..ranges.len() {
+            for j in i + 1..ranges.len() {
+                if ranges[i].intersects(&ranges[j]) {
+                    neighbours[i].insert(j);
+                    neighbours[j].insert(i);
+                }
+            }
+        }
+
+        OverlapGraph { neighbours }
+    }
+
+    pub fn overlaps(&self, a: usize, b: usize) -> bool {
+        self.neighbours[a].contains(&b)
+    }
+
+    pub fn degree(&self, nanobot: usize) -> usize {
+        self.neighbours[nanobot].len()
+    }
+
+    /// A set of nanobots whose ranges all overlap each other, built greedily from the nanobots
+    /// that overlap the most others.  Not necessarily the largest set, but usually close.
+    pub fn greedy_clique(&self) -> Vec<usize> {
+        let mut order = (0..self.neighbours.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&nanobot| std::cmp::Reverse(self.degree(nanobot)));
+
+        let mut clique: Vec<usize> = vec![];
+        for nanobot in order {
+            if clique.iter().all(|&other| self.overlaps(nanobot, other)) {
+                clique.push(nanobot);
+            }
+        }
+        clique.sort();
+        clique
+    }
+
+    /// No set of mutually overlapping nanobots can be bigger than this: a set of n needs n
+    /// nanobots that each overlap at least n - 1 others.
+    pub fn clique_bound(&self) -> usize {
+        let mut degrees = self.neighbours.iter().map(|n| n.len()).collect::<Vec<_>>();
+        degrees.sort_by(|a, b| b.cmp(a));
+
+        degrees
+            .iter()
+            .enumerate()
+            .take_while(|&(i, &degree)| degree >= i)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nanobot::tests::nanobots;
+
+    fn point(x: isize, y: isize, z: isize) -> Point {
+        Point { x, y, z }
+    }
+
+    #[test]
+    fn test_octahedron() {
+        let range = Octahedron { centre: point(1, 1, 1), radius: 3 };
+        assert!(range.contains(&point(1, 1, 1)));
+        assert!(range.contains(&point(2, -1, 1)));
+        assert!(!range.contains(&point(2, -1, 0)));
+
+        // Just touching at (4, 1, 1)
+        let touching = Octahedron { centre: point(6, 1, 1), radius: 2 };
+        assert!(range.intersects(&touching));
+        assert!(touching.intersects(&range));
+        assert!(!range.intersects(&Octahedron { centre: point(6, 1, 0), radius: 2 }));
+    }
+
+    #[test]
+    fn test_cube() {
+        let cube = Cube { corner: Point::ORIGIN, size: 4 };
+        assert_eq!(cube.distance_to(&point(1, 2, 3)), 0);
+        assert_eq!(cube.distance_to(&point(-1, 5, 3)), 1 + 2);
+        assert_eq!(cube.split().len(), 8);
+        assert!(cube.split().contains(&Cube { corner: point(2, 0, 2), size: 2 }));
+
+        let range = Octahedron { centre: point(8, 0, 0), radius: 3 };
+        assert_eq!(range.distance_to_cube(&cube), 2);
+        let range = Octahedron { centre: point(8, 0, 0), radius: 5 };
+        assert_eq!(range.distance_to_cube(&cube), 0);
+
+        let bots = nanobots(&["pos=<1,1,1>, r=1", "pos=<8,0,0>, r=4", "pos=<8,0,0>, r=5"]);
+        assert_eq!(Cube::bounding(&bots), Cube { corner: point(0, -5, -5), size: 16 });
+    }
+
+    #[test]
+    fn test_overlap_graph() {
+        let graph = OverlapGraph::new(&nanobots(&[
+            "pos=<0,0,0>, r=2",
+            "pos=<3,0,0>, r=1",
+            "pos=<0,4,0>, r=2",
+            "pos=<1,2,0>, r=1",
+            "pos=<20,20,20>, r=1",
+        ]));
+
+        assert!(graph.overlaps(0, 1));
+        assert!(!graph.overlaps(1, 2));
+        assert_eq!(graph.degree(0), 3);
+        assert_eq!(graph.degree(4), 0);
+
+        assert_eq!(graph.greedy_clique(), vec![0, 2, 3]);
+        assert_eq!(graph.clique_bound(), 3);
+    }
+}
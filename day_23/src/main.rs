@@ -1,14 +1,25 @@
+mod geometry;
 mod nanobot;
+mod search;
 
+use std::env;
+
+use crate::geometry::{OverlapGraph, Point};
 use crate::nanobot::Nanobot;
 
 fn main() {
-    let nanobots = include_str!("input.txt")
+    let nanobots: Vec<Nanobot> = include_str!("input.txt")
         .trim()
         .split("\n")
         .map(|s| Nanobot::parse(&s.to_string()))
         .collect();
 
+    // `cargo run -- overlaps` compares how many nanobots' ranges overlap with how many can
+    // actually be reached from one point
+    if env::args().nth(1).as_deref() == Some("overlaps") {
+        return overlaps(&nanobots);
+    }
+
     println!("part_1: {}", part_1(&nanobots));
     println!("part_2: {}", part_2(&nanobots));
 }
@@ -25,8 +36,21 @@ fn part_1(nanobots: &Vec<Nanobot>) -> usize {
         .count()
 }
 
+// Find the coordinates in range of the most nanobots, and of those the closest to the origin.
+// What's its distance from the origin?
 fn part_2(nanobots: &Vec<Nanobot>) -> usize {
-    2
+    let (point, _count) = search::most_in_range(nanobots).unwrap();
+    point.distance(&Point::ORIGIN) as usize
+}
+
+fn overlaps(nanobots: &[Nanobot]) {
+    let graph = OverlapGraph::new(nanobots);
+    let clique = graph.greedy_clique();
+    let (point, count) = search::most_in_range(nanobots).unwrap();
+
+    println!("Most mutually overlapping nanobots: at least {}", clique.len());
+    println!("Most mutually overlapping nanobots: at most {}", graph.clique_bound());
+    println!("Most in range of one point: {}, at {:?}", count, point);
 }
 
 #[cfg(test)]
@@ -53,4 +77,22 @@ mod tests {
 
         assert_eq!(part_1(&nanobots), 7);
     }
+
+    #[test]
+    fn test_part_2() {
+        let input = [
+            "pos=<10,12,12>, r=2",
+            "pos=<12,14,12>, r=2",
+            "pos=<16,12,12>, r=4",
+            "pos=<14,14,14>, r=6",
+            "pos=<50,50,50>, r=200",
+            "pos=<10,10,10>, r=5",
+        ];
+        let nanobots = input
+            .iter()
+            .map(|s| Nanobot::parse(&s.to_string()))
+            .collect();
+
+        assert_eq!(part_2(&nanobots), 36);
+    }
 }
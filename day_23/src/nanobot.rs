@@ -1,5 +1,7 @@
 use regex::Regex;
 
+use crate::geometry::{Octahedron, Point};
+
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub struct Nanobot {
     pub x: isize,
@@ -21,20 +23,28 @@ impl Nanobot {
         }
     }
 
+    pub fn position(&self) -> Point {
+        Point { x: self.x, y: self.y, z: self.z }
+    }
+
+    /// Every point the nanobot's signal reaches
+    pub fn range(&self) -> Octahedron {
+        Octahedron { centre: self.position(), radius: self.r }
+    }
+
     pub fn in_range(&self, nanobot: &Nanobot) -> bool {
-        let distance = (
-            (nanobot.x - self.x).abs() +
-            (nanobot.y - self.y).abs() +
-            (nanobot.z - self.z).abs()
-        );
-        distance <= self.r
+        self.range().contains(&nanobot.position())
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use super::*;
 
+    pub fn nanobots(lines: &[&str]) -> Vec<Nanobot> {
+        lines.iter().map(|line| Nanobot::parse(&line.to_string())).collect()
+    }
+
     #[test]
     fn test_nanobot_parse() {
         assert_eq!(Nanobot::parse(&"pos=<2,-1,4>, r=3".to_string()), Nanobot {
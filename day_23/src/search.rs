@@ -0,0 +1,79 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::geometry::{Cube, Point};
+use crate::nanobot::Nanobot;
+
+/// The point in range of the most nanobots, and how many that is.  Ties go to the point closest
+/// to the origin.
+///
+/// Searches cubes best first, ordered by how many nanobots could reach a point in them, then how
+/// close they could be to the origin.  Both are bounds that only get tighter as cubes are split, so
+/// the first single point to come out of the queue can't be beaten by anything left in it.
+pub fn most_in_range(nanobots: &[Nanobot]) -> Option<(Point, usize)> {
+    if nanobots.is_empty() {
+        return None;
+    }
+
+    let mut queue = BinaryHeap::new();
+    let ranges = nanobots.iter().map(|nb| nb.range()).collect::<Vec<_>>();
+    let push = |queue: &mut BinaryHeap<_>, cube: Cube| {
+        // No single point in the cube can be in range of more nanobots than reach any of it, and
+        // for a cube of one point it's exact
+        let count = ranges.iter().filter(|range| range.distance_to_cube(&cube) == 0).count();
+        let distance = cube.distance_to(&Point::ORIGIN);
+        queue.push((count, Reverse(distance), Reverse(cube.size), Reverse(cube)));
+    };
+
+    push(&mut queue, Cube::bounding(nanobots));
+
+    while let Some((count, _distance, _size, Reverse(cube))) = queue.pop() {
+        if cube.size == 1 {
+            return Some((cube.corner, count));
+        }
+
+        for child in cube.split() {
+            push(&mut queue, child);
+        }
+    }
+
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nanobot::tests::nanobots;
+
+    #[test]
+    fn test_most_in_range() {
+        let example = nanobots(&[
+            "pos=<10,12,12>, r=2",
+            "pos=<12,14,12>, r=2",
+            "pos=<16,12,12>, r=4",
+            "pos=<14,14,14>, r=6",
+            "pos=<50,50,50>, r=200",
+            "pos=<10,10,10>, r=5",
+        ]);
+        assert_eq!(most_in_range(&example), Some((Point { x: 12, y: 12, z: 12 }, 5)));
+        assert_eq!(most_in_range(&[]), None);
+    }
+
+    #[test]
+    fn test_most_in_range_ties() {
+        // Two bots that can't both be reached: the nearer one wins
+        let apart = nanobots(&["pos=<20,0,0>, r=3", "pos=<-9,-9,-9>, r=1"]);
+        assert_eq!(most_in_range(&apart), Some((Point { x: 17, y: 0, z: 0 }, 1)));
+
+        // The origin itself is in range of everything
+        let around = nanobots(&["pos=<3,0,0>, r=5", "pos=<0,-4,2>, r=6", "pos=<1,1,1>, r=3"]);
+        assert_eq!(most_in_range(&around), Some((Point::ORIGIN, 3)));
+
+        // A lone bot far from the origin in a negative corner, whose nearest points are a whole
+        // face of its range
+        let corner = nanobots(&["pos=<-100,-100,-100>, r=10"]);
+        let (point, count) = most_in_range(&corner).unwrap();
+        assert_eq!((point.distance(&Point::ORIGIN), count), (290, 1));
+        assert!(corner[0].range().contains(&point));
+    }
+}
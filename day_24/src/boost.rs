@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::groups::{Battle, Group, Outcome};
+
+/// No boost beyond this is tried.  If the immune system can't win by then, some infection group
+/// must be immune to everything it has.
+const MAX_BOOST: usize = 1 << 24;
+
+/// Fights a battle with every immune group's attack damage raised by the boost
+pub fn fight_boosted(immune_groups: &[Group], infection_groups: &[Group], boost: usize) -> Outcome {
+    let mut immune_groups = immune_groups.to_vec();
+    immune_groups.iter_mut().for_each(|g| g.attack_damage += boost);
+
+    Battle::new(immune_groups, infection_groups.to_vec()).fight()
+}
+
+/// How much work a BoostSearch has done
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Stats {
+    pub fights: usize,
+    pub rounds: usize,
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} fights, {} rounds, {:?}", self.fights, self.rounds, self.elapsed)?;
+        if self.fights > 0 {
+            write!(f, " ({:?} per fight)", self.elapsed / self.fights as u32)?;
+        }
+        Ok(())
+    }
+}
+
+/// Looks for the smallest boost the immune system needs to win, without fighting every boost.
+///
+/// Bigger boosts usually help, so it gallops up through powers of two until the immune system
+/// wins, then binary searches down between the last loss and that win.  But it isn't strictly
+/// true: a boost can stall with the last groups unable to hurt each other while a slightly smaller
+/// one loses outright, or one side can lose a group a round earlier and swing the battle.  So
+/// every boost between the gallop's last loss and the binary search's answer is fought too, and
+/// the smallest win among them is the answer.  Boosts below the gallop's last loss are taken to
+/// lose, like the powers of two fought on the way up.
+pub struct BoostSearch<'a> {
+    fight: Box<dyn FnMut(usize) -> Outcome + 'a>,
+    outcomes: BTreeMap<usize, Outcome>,
+    pub stats: Stats,
+}
+
+impl<'a> BoostSearch<'a> {
+    pub fn new(immune_groups: &'a [Group], infection_groups: &'a [Group]) -> BoostSearch<'a> {
+        BoostSearch::with_fight(move |boost| fight_boosted(immune_groups, infection_groups, boost))
+    }
+
+    /// Searches over whatever the fight gives for each boost
+    fn with_fight(fight: impl FnMut(usize) -> Outcome + 'a) -> BoostSearch<'a> {
+        BoostSearch {
+            fight: Box::new(fight),
+            outcomes: BTreeMap::new(),
+            stats: Stats::default(),
+        }
+    }
+
+    /// The outcome with the given boost, only fighting the battle the first time it's asked for
+    pub fn outcome(&mut self, boost: usize) -> Outcome {
+        if let Some(outcome) = self.outcomes.get(&boost) {
+            return *outcome;
+        }
+
+        let start = Instant::now();
+        let outcome = (self.fight)(boost);
+        self.stats.elapsed += start.elapsed();
+        self.stats.fights += 1;
+        self.stats.rounds += match outcome {
+            Outcome::ImmuneWins { rounds, .. }
+            | Outcome::InfectionWins { rounds, .. }
+            | Outcome::Stalemate { rounds, .. } => rounds,
+        };
+
+        self.outcomes.insert(boost, outcome);
+        outcome
+    }
+
+    fn immune_wins(&mut self, boost: usize) -> bool {
+        match self.outcome(boost) {
+            Outcome::ImmuneWins { .. } => true,
+            Outcome::InfectionWins { .. } | Outcome::Stalemate { .. } => false,
+        }
+    }
+
+    /// The smallest boost found that lets the immune system win, and how many units it has left
+    pub fn smallest_winning_boost(&mut self) -> Option<(usize, usize)> {
+        if self.immune_wins(0) {
+            return self.winner(0);
+        }
+
+        // Gallop until the immune system wins
+        let (mut lose, mut win) = (0, 1);
+        while !self.immune_wins(win) {
+            if win >= MAX_BOOST {
+                return None;
+            }
+            lose = win;
+            win *= 2;
+        }
+
+        // Binary search between a losing and a winning boost
+        let gallop_lose = lose;
+        while win - lose > 1 {
+            let boost = lose + (win - lose) / 2;
+            if self.immune_wins(boost) {
+                win = boost;
+            } else {
+                lose = boost;
+            }
+        }
+
+        // The binary search only knows each boost it fought loses, not the ones it jumped over,
+        // so fight the rest of the gallop's interval for a smaller win
+        let smallest = (gallop_lose + 1..win)
+            .find(|&boost| self.immune_wins(boost))
+            .unwrap_or(win);
+        self.winner(smallest)
+    }
+
+    fn winner(&mut self, boost: usize) -> Option<(usize, usize)> {
+        match self.outcome(boost) {
+            Outcome::ImmuneWins { units, .. } => Some((boost, units)),
+            Outcome::InfectionWins { .. } | Outcome::Stalemate { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::groups::ArmyType;
+
+    #[test]
+    fn test_boost_search() {
+        let (immune_groups, infection_groups) = crate::tests::example_groups();
+        let mut search = BoostSearch::new(&immune_groups, &infection_groups);
+
+        assert_eq!(search.smallest_winning_boost(), Some((1570, 51)));
+        assert!(search.stats.fights < 600);
+        assert!(search.stats.rounds > search.stats.fights);
+
+        // Already fought, so nothing more to do
+        let fights = search.stats.fights;
+        assert_eq!(search.outcome(1570), Outcome::ImmuneWins { units: 51, rounds: 41 });
+        assert_eq!(search.stats.fights, fights);
+    }
+
+    #[test]
+    fn test_boost_search_hopeless() {
+        // No boost can hurt an infection group that's immune to the only attack type
+        let immune_groups = [Group::parse(
+            ArmyType::Immune,
+            "10 units each with 10 hit points with an attack that does 5 radiation damage at initiative 2",
+        ).unwrap()];
+        let infection_groups = [Group::parse(
+            ArmyType::Infection,
+            "10 units each with 10 hit points (immune to radiation) with an attack that does 1 fire damage at initiative 1",
+        ).unwrap()];
+
+        let mut search = BoostSearch::new(&immune_groups, &infection_groups);
+        assert_eq!(search.smallest_winning_boost(), None);
+        assert_eq!(search.stats.fights, 26);
+    }
+
+    #[test]
+    fn test_boost_search_not_monotonic() {
+        // Boosts from 1000 up win, but so do a few far below that, with stalemates either side
+        let outcome = |boost: usize| match boost {
+            600..=610 | 1000.. => Outcome::ImmuneWins { units: boost, rounds: 1 },
+            590..=620 => Outcome::Stalemate { immune_units: 1, infection_units: 1, rounds: 1 },
+            _ => Outcome::InfectionWins { units: 1, rounds: 1 },
+        };
+        let mut search = BoostSearch::with_fight(outcome);
+
+        // Galloping stops at 1024 and binary searching finds 1000, but 600 is smaller
+        assert_eq!(search.smallest_winning_boost(), Some((600, 600)));
+        assert_eq!(search.outcome(1000), Outcome::ImmuneWins { units: 1000, rounds: 1 });
+        assert!(search.stats.fights < 1000);
+    }
+}
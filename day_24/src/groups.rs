@@ -1,127 +1,196 @@
+use lazy_static::lazy_static;
 use regex::Regex;
 use std::cmp::Reverse;
-use std::collections::{HashSet, HashMap};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::fmt;
+use std::collections::HashSet;
 
+/// Both armies' groups in one arena.  Groups are referred to by their index into it, and stay
+/// there once they've been wiped out, just no longer alive.
 pub struct Battle {
-    pub immune_groups: Vec<Group>,
-    pub infection_groups: Vec<Group>,
+    groups: Vec<Group>,
+    alive: Vec<bool>,
+    /// Each group's number within its army, counting from 1 in the order they were given, which
+    /// is how the battle log refers to them
+    numbers: Vec<usize>,
+}
+
+/// How a battle ended, with the units left standing and how many rounds it took
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Outcome {
+    ImmuneWins { units: usize, rounds: usize },
+    InfectionWins { units: usize, rounds: usize },
+    /// A round went by without any units dying, so every round after it will too
+    Stalemate { immune_units: usize, infection_units: usize, rounds: usize },
 }
 
 impl Battle {
-    pub fn fight(&mut self) {
-        let mut done = false;
-
-        while !done {
-            // TARGET SELECTION PHASE
-            let mut target_selection_order: Vec<&Group> = self.immune_groups
-                .iter()
-                .chain(self.infection_groups.iter())
-                .collect();
-            target_selection_order.sort_by_key(|g| Reverse((g.effective_power(), g.initiative)));
-
-            let mut targets = HashMap::new();
-            let mut chosen = HashSet::new();
-
-            for group in target_selection_order {
-                let mut target_groups = match group.army_type {
-                    ArmyType::Immune => self.infection_groups.clone(),
-                    ArmyType::Infection => self.immune_groups.clone(),
-                };
-
-                target_groups.sort_by_key(|tg| Reverse((
-                    Battle::damage(group, tg),
-                    tg.effective_power(),
-                    tg.initiative,
-                )));
-
-                for target_group in target_groups {
-                    if Battle::damage(group, &target_group) == 0 {
-                        continue;
-                    }
-
-                    if chosen.contains(&(target_group.army_type, target_group.id)) {
-                        continue;
-                    }
-
-                    targets.insert((group.army_type, group.id), (target_group.army_type, target_group.id));
-                    chosen.insert((target_group.army_type, target_group.id));
-                    break;
-                }
+    pub fn new(immune_groups: Vec<Group>, infection_groups: Vec<Group>) -> Battle {
+        let numbers = (1..=immune_groups.len()).chain(1..=infection_groups.len()).collect();
+        let groups: Vec<Group> = immune_groups.into_iter().chain(infection_groups).collect();
+        let alive = groups.iter().map(|g| g.units > 0).collect();
+
+        Battle { groups, alive, numbers }
+    }
+
+    /// The groups of an army that still have units
+    pub fn groups(&self, army_type: ArmyType) -> impl Iterator<Item = &Group> {
+        self.living().map(move |i| &self.groups[i]).filter(move |g| g.army_type == army_type)
+    }
+
+    pub fn units(&self, army_type: ArmyType) -> usize {
+        self.groups(army_type).map(|g| g.units).sum()
+    }
+
+    fn living(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.groups.len()).filter(move |&i| self.alive[i])
+    }
+
+    /// Fights until one army is wiped out, or until neither army can kill any more units
+    pub fn fight(&mut self) -> Outcome {
+        self.fight_logged(&mut |_| ())
+    }
+
+    /// Fights like Battle#fight, passing each line of the puzzle's battle log to `log` as it goes:
+    /// both armies at the start of every round, the damage each group would deal while choosing
+    /// its target, every attack, and both armies at the end
+    pub fn fight_logged(&mut self, log: &mut dyn FnMut(&str)) -> Outcome {
+        let mut rounds = 0;
+
+        loop {
+            self.log_armies(log);
+
+            let immune_units = self.units(ArmyType::Immune);
+            let infection_units = self.units(ArmyType::Infection);
+
+            if immune_units == 0 {
+                return Outcome::InfectionWins { units: infection_units, rounds };
             }
+            if infection_units == 0 {
+                return Outcome::ImmuneWins { units: immune_units, rounds };
+            }
+
+            log("");
+            let killed = self.round(log);
+            rounds += 1;
+            log("");
 
-            // BATTLE PHASE
-            let mut battle_order: Vec<Group> = self.immune_groups
-                .iter()
-                .chain(self.infection_groups.iter())
-                .map(|g| g.clone())
-                .collect();
-            battle_order.sort_by_key(|g| Reverse(g.initiative));
-
-            let mut dead = HashSet::new();
-            let mut new_units = HashMap::new();
-
-            for group in &mut battle_order {
-                // Skip if no target
-                let target_group = targets.get(&(group.army_type, group.id));
-                if target_group.is_none() { continue }
-                let (target_group_type, target_group_id) = target_group.unwrap();
-
-                // Get the actual group
-                let groups = match target_group_type {
-                    ArmyType::Immune => &mut self.immune_groups,
-                    ArmyType::Infection => &mut self.infection_groups,
-                };
-                let target_group = groups.iter_mut().find(|g| g.id == *target_group_id);
-                if target_group.is_none() { continue }
-                let target_group = target_group.unwrap();
-
-                // Skip if this group is dead
-                if dead.contains(&(group.army_type, group.id)) { continue }
-
-                // Mutate this group's unit count
-                if let Some(updated_units) = new_units.get(&(group.army_type, group.id)) {
-                    group.units = *updated_units;
-                }
-
-                let damage = Battle::damage(&group, &target_group);
-                let group_hp = target_group.units * target_group.hit_points;
-
-                if damage >= group_hp {
-                    dead.insert((target_group.army_type, target_group.id));
-                } else {
-                    let units = (group_hp - damage + target_group.hit_points - 1) / target_group.hit_points;
-                    new_units.insert((target_group.army_type, target_group.id), units);
-                }
+            if killed == 0 {
+                self.log_armies(log);
+                return Outcome::Stalemate { immune_units, infection_units, rounds };
             }
+        }
+    }
 
-            for (army_type, id) in &dead {
-                let groups = match army_type {
-                    ArmyType::Immune => &mut self.immune_groups,
-                    ArmyType::Infection => &mut self.infection_groups,
-                };
+    fn log_armies(&self, log: &mut dyn FnMut(&str)) {
+        for &army_type in &[ArmyType::Immune, ArmyType::Infection] {
+            log(&format!("{}:", army_type));
 
-                if let Some(group) = groups.iter().find(|g| g.id == *id) {
-                    let group = group.clone();
-                    groups.remove_item(&group);
-                }
+            let mut remain = false;
+            for i in self.living().filter(|&i| self.groups[i].army_type == army_type) {
+                log(&format!("Group {} contains {} units", self.numbers[i], self.groups[i].units));
+                remain = true;
             }
+            if !remain {
+                log("No groups remain.");
+            }
+        }
+    }
 
-            for ((army_type, id), units) in &new_units {
-                if let Some(group) = self.get_group_mut(*army_type, *id) {
-                    group.units = *units;
-                }
+    /// Fights one round, returning how many units died
+    fn round(&mut self, log: &mut dyn FnMut(&str)) -> usize {
+        // TARGET SELECTION PHASE
+        let mut selection_order: Vec<usize> = self.living().collect();
+        selection_order.sort_by_key(|&i| {
+            Reverse((self.groups[i].effective_power(), self.groups[i].initiative))
+        });
+
+        let mut targets = vec![None; self.groups.len()];
+        let mut chosen = vec![false; self.groups.len()];
+        // Each group's damage to every target it could still pick when choosing
+        let mut would_deal = vec![];
+
+        for attacker in selection_order {
+            let group = &self.groups[attacker];
+            let candidates = self
+                .living()
+                .filter(|&i| self.groups[i].army_type != group.army_type && !chosen[i])
+                .filter(|&i| Battle::damage(group, &self.groups[i]) > 0)
+                .collect::<Vec<_>>();
+
+            for &i in &candidates {
+                would_deal.push((attacker, i, Battle::damage(group, &self.groups[i])));
             }
 
-            done = self.immune_groups.is_empty() || self.infection_groups.is_empty();
+            let target = candidates
+                .into_iter()
+                .max_by_key(|&i| {
+                    let target_group = &self.groups[i];
+                    (
+                        Battle::damage(group, target_group),
+                        target_group.effective_power(),
+                        target_group.initiative,
+                    )
+                });
+
+            if let Some(target) = target {
+                targets[attacker] = Some(target);
+                chosen[target] = true;
+            }
         }
-    }
 
-    fn get_group_mut(&mut self, army_type: ArmyType, id: usize) -> Option<&mut Group> {
-        match army_type {
-            ArmyType::Immune => self.immune_groups.iter_mut().find(|g| g.id == id),
-            ArmyType::Infection => self.infection_groups.iter_mut().find(|g| g.id == id),
+        // The puzzle lists the infection's groups first, then goes by group number
+        would_deal.sort_by_key(|&(attacker, target, _damage)| {
+            let army_type = self.groups[attacker].army_type;
+            (army_type == ArmyType::Immune, self.numbers[attacker], self.numbers[target])
+        });
+        for (attacker, target, damage) in would_deal {
+            log(&format!(
+                "{} group {} would deal defending group {} {} damage",
+                self.groups[attacker].army_type,
+                self.numbers[attacker],
+                self.numbers[target],
+                damage,
+            ));
         }
+        log("");
+
+        // ATTACK PHASE
+        let mut attack_order: Vec<usize> = self.living().collect();
+        attack_order.sort_by_key(|&i| Reverse(self.groups[i].initiative));
+
+        let mut killed = 0;
+
+        for attacker in attack_order {
+            // Groups wiped out earlier in the round don't get to attack
+            if !self.alive[attacker] {
+                continue;
+            }
+
+            let target = match targets[attacker] {
+                Some(target) => target,
+                None => continue,
+            };
+
+            let damage = Battle::damage(&self.groups[attacker], &self.groups[target]);
+            let target_group = &mut self.groups[target];
+            let units_killed = (damage / target_group.hit_points).min(target_group.units);
+
+            target_group.units -= units_killed;
+            killed += units_killed;
+            if target_group.units == 0 {
+                self.alive[target] = false;
+            }
+
+            log(&format!(
+                "{} group {} attacks defending group {}, killing {} units",
+                self.groups[attacker].army_type,
+                self.numbers[attacker],
+                self.numbers[target],
+                units_killed,
+            ));
+        }
+
+        killed
     }
 
     fn damage(attacking_group: &Group, defending_group: &Group) -> usize {
@@ -137,7 +206,6 @@ impl Battle {
 
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Group {
-    pub id: usize,
     pub army_type: ArmyType,
     pub units: usize,
     pub hit_points: usize,
@@ -148,67 +216,175 @@ pub struct Group {
     pub immunities: HashSet<AttackType>,
 }
 
-static IMMUNE_GROUP_ID: AtomicUsize = AtomicUsize::new(1);
-static INFECTION_GROUP_ID: AtomicUsize = AtomicUsize::new(1);
-
-impl Group {
-    pub fn parse(army_type: ArmyType, line: &str) -> Group {
-        let re = Regex::new(r"(?P<units>\d+) units each with (?P<hit_points>\d+) hit points.*?with an attack that does (?P<attack_damage>\d+) (?P<attack_type>\w+) damage at initiative (?P<initiative>\d+)").unwrap();
-        let caps = re.captures(line).unwrap();
+/// A problem with an army file, along with the line it was found on
+#[derive(Eq, PartialEq, Debug)]
+pub enum ParseError {
+    /// A group before any "Immune System:" or "Infection:" header
+    MissingHeader { line: String },
+    /// A header for neither army, e.g. "Undead:"
+    UnknownHeader { line: String },
+    /// Not a group, or a group with a number too big to hold
+    MalformedGroup { line: String },
+    UnknownAttackType { line: String, attack_type: String },
+}
 
-        let weaknesses = Group::parse_attacks(
-            Regex::new(r"\(.*weak to ([\w,\s]+?)[;)]").unwrap(),
-            line,
-        );
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingHeader { line } => {
+                write!(f, "\"{}\" isn't under an army header", line)
+            },
+            ParseError::UnknownHeader { line } => {
+                write!(f, "\"{}\" isn't \"Immune System:\" or \"Infection:\"", line)
+            },
+            ParseError::MalformedGroup { line } => write!(f, "\"{}\" isn't a group", line),
+            ParseError::UnknownAttackType { line, attack_type } => {
+                write!(f, "\"{}\": unknown attack type {}", line, attack_type)
+            },
+        }
+    }
+}
 
-        let immunities = Group::parse_attacks(
-            Regex::new(r"\(.*immune to ([\w,\s]+?)[;)]").unwrap(),
-            line,
-        );
+/// Parses a whole army file: an "Immune System:" section and an "Infection:" section, each a
+/// list of groups, one per line
+pub fn parse_armies(input: &str) -> Result<(Vec<Group>, Vec<Group>), ParseError> {
+    let mut immune_groups = vec![];
+    let mut infection_groups = vec![];
+    let mut army_type = None;
+
+    for line in input.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+        if let Some(header) = line.strip_suffix(':') {
+            army_type = match header {
+                "Immune System" => Some(ArmyType::Immune),
+                "Infection" => Some(ArmyType::Infection),
+                _ => return Err(ParseError::UnknownHeader { line: line.to_string() }),
+            };
+            continue;
+        }
 
-        let id = match army_type {
-            ArmyType::Immune => IMMUNE_GROUP_ID.fetch_add(1, Ordering::SeqCst),
-            ArmyType::Infection => INFECTION_GROUP_ID.fetch_add(1, Ordering::SeqCst),
+        let group = match army_type {
+            Some(army_type) => Group::parse(army_type, line)?,
+            None => return Err(ParseError::MissingHeader { line: line.to_string() }),
         };
+        match group.army_type {
+            ArmyType::Immune => immune_groups.push(group),
+            ArmyType::Infection => infection_groups.push(group),
+        }
+    }
 
-        Group {
-            id,
+    Ok((immune_groups, infection_groups))
+}
+
+/// Writes both armies out the way parse_armies reads them
+pub fn format_armies(immune_groups: &[Group], infection_groups: &[Group]) -> String {
+    let mut output = String::new();
+
+    let armies = [(ArmyType::Immune, immune_groups), (ArmyType::Infection, infection_groups)];
+    for (army_type, groups) in &armies {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output.push_str(&format!("{}:\n", army_type));
+        for group in groups.iter() {
+            output.push_str(&format!("{}\n", group));
+        }
+    }
+
+    output
+}
+
+impl Group {
+    pub fn parse(army_type: ArmyType, line: &str) -> Result<Group, ParseError> {
+        lazy_static! {
+            static ref GROUP_REGEX: Regex = Regex::new(r"^(?P<units>\d+) units each with (?P<hit_points>\d+) hit points.*?with an attack that does (?P<attack_damage>\d+) (?P<attack_type>\w+) damage at initiative (?P<initiative>\d+)$").unwrap();
+            static ref WEAKNESS_REGEX: Regex = Regex::new(r"\(.*weak to ([\w,\s]+?)[;)]").unwrap();
+            static ref IMMUNITY_REGEX: Regex = Regex::new(r"\(.*immune to ([\w,\s]+?)[;)]").unwrap();
+        }
+
+        let malformed = || ParseError::MalformedGroup { line: line.to_string() };
+        let caps = GROUP_REGEX.captures(line).ok_or_else(malformed)?;
+        // Only digits get this far, but there can be too many of them
+        let number = |name: &str| caps[name].parse::<usize>().map_err(|_| malformed());
+
+        let weaknesses = Group::parse_attacks(&WEAKNESS_REGEX, line)?;
+        let immunities = Group::parse_attacks(&IMMUNITY_REGEX, line)?;
+
+        Ok(Group {
             army_type,
-            units: caps["units"].parse().unwrap(),
-            hit_points: caps["hit_points"].parse().unwrap(),
-            attack_damage: caps["attack_damage"].parse().unwrap(),
-            attack_type: AttackType::from_string(&caps["attack_type"]),
-            initiative: caps["initiative"].parse().unwrap(),
+            units: number("units")?,
+            hit_points: number("hit_points")?,
+            attack_damage: number("attack_damage")?,
+            attack_type: Group::parse_attack(line, &caps["attack_type"])?,
+            initiative: number("initiative")?,
             weaknesses,
             immunities,
-        }
+        })
     }
 
-    fn parse_attacks(regex: Regex, line: &str) -> HashSet<AttackType> {
+    fn parse_attacks(regex: &Regex, line: &str) -> Result<HashSet<AttackType>, ParseError> {
         match regex.captures(line) {
-            Some(cap) => {
-                cap[1]
-                    .to_string()
-                    .split(", ")
-                    .map(|s| AttackType::from_string(s))
-                    .collect()
-            },
-            None => [].iter().cloned().collect(),
+            Some(cap) => cap[1].split(", ").map(|s| Group::parse_attack(line, s)).collect(),
+            None => Ok(HashSet::new()),
         }
     }
 
+    fn parse_attack(line: &str, attack_type: &str) -> Result<AttackType, ParseError> {
+        AttackType::from_string(attack_type).ok_or_else(|| ParseError::UnknownAttackType {
+            line: line.to_string(),
+            attack_type: attack_type.to_string(),
+        })
+    }
+
     pub fn effective_power(&self) -> usize {
         self.units * self.attack_damage
     }
 }
 
+/// The group as a line of an army file, with its immunities and weaknesses in alphabetical order
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} units each with {} hit points", self.units, self.hit_points)?;
+
+        let list = |attack_types: &HashSet<AttackType>| {
+            let mut attack_types = attack_types.iter().collect::<Vec<_>>();
+            attack_types.sort();
+            attack_types.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", ")
+        };
+        let mut modifiers = vec![];
+        if !self.immunities.is_empty() {
+            modifiers.push(format!("immune to {}", list(&self.immunities)));
+        }
+        if !self.weaknesses.is_empty() {
+            modifiers.push(format!("weak to {}", list(&self.weaknesses)));
+        }
+        if !modifiers.is_empty() {
+            write!(f, " ({})", modifiers.join("; "))?;
+        }
+
+        write!(
+            f,
+            " with an attack that does {} {} damage at initiative {}",
+            self.attack_damage, self.attack_type, self.initiative,
+        )
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum ArmyType {
     Immune,
     Infection,
 }
 
-#[derive(Copy, Clone, Hash, Eq, PartialEq, Debug)]
+impl fmt::Display for ArmyType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArmyType::Immune => write!(f, "Immune System"),
+            ArmyType::Infection => write!(f, "Infection"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Debug)]
 pub enum AttackType {
     Bludgeoning,
     Cold,
@@ -218,20 +394,33 @@ pub enum AttackType {
 }
 
 impl AttackType {
-    pub fn from_string(attack_type: &str) -> AttackType {
+    pub fn from_string(attack_type: &str) -> Option<AttackType> {
         use AttackType::*;
 
         match attack_type {
-            "bludgeoning" => Bludgeoning,
-            "cold" => Cold,
-            "fire" => Fire,
-            "radiation" => Radiation,
-            "slashing" => Slashing,
-            _ => panic!("Dunno what is {}", attack_type),
+            "bludgeoning" => Some(Bludgeoning),
+            "cold" => Some(Cold),
+            "fire" => Some(Fire),
+            "radiation" => Some(Radiation),
+            "slashing" => Some(Slashing),
+            _ => None,
         }
     }
 }
 
+impl fmt::Display for AttackType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AttackType::Bludgeoning => "bludgeoning",
+            AttackType::Cold => "cold",
+            AttackType::Fire => "fire",
+            AttackType::Radiation => "radiation",
+            AttackType::Slashing => "slashing",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -241,7 +430,7 @@ mod tests {
         use ArmyType::*;
         use AttackType::*;
 
-        let group = Group::parse(Immune, &"18 units each with 729 hit points (weak to fire; immune to cold, slashing) with an attack that does 8 radiation damage at initiative 10");
+        let group = Group::parse(Immune, "18 units each with 729 hit points (weak to fire; immune to cold, slashing) with an attack that does 8 radiation damage at initiative 10").unwrap();
 
         assert_eq!(group.army_type, Immune);
         assert_eq!(group.units, 18);
@@ -253,22 +442,178 @@ mod tests {
         assert_eq!(group.immunities, [Cold, Slashing].iter().cloned().collect());
     }
 
+    #[test]
+    fn test_group_display() {
+        let line = "18 units each with 729 hit points (immune to cold, slashing; weak to fire) with an attack that does 8 radiation damage at initiative 10";
+        assert_eq!(Group::parse(ArmyType::Immune, line).unwrap().to_string(), line);
+
+        let line = "442 units each with 26430 hit points with an attack that does 116 bludgeoning damage at initiative 13";
+        assert_eq!(Group::parse(ArmyType::Infection, line).unwrap().to_string(), line);
+
+        // Weaknesses and immunities come out in the same order however they went in
+        let line = "1 units each with 2 hit points (weak to fire, cold; immune to slashing) with an attack that does 3 cold damage at initiative 4";
+        assert_eq!(
+            Group::parse(ArmyType::Infection, line).unwrap().to_string(),
+            "1 units each with 2 hit points (immune to slashing; weak to cold, fire) with an attack that does 3 cold damage at initiative 4",
+        );
+    }
+
+    #[test]
+    fn test_group_round_trip() {
+        use AttackType::*;
+        let attack_types = [Bludgeoning, Cold, Fire, Radiation, Slashing];
+
+        // Every way of splitting the attack types between weak, immune and neither, with a spread
+        // of numbers from a simple LCG
+        let mut seed: usize = 24;
+        let mut next = |max: usize| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) % max + 1
+        };
+
+        for split in 0..3usize.pow(attack_types.len() as u32) {
+            let modifier = |i: usize| split / 3usize.pow(i as u32) % 3;
+            let with = |m: usize| {
+                (0..attack_types.len())
+                    .filter(|&i| modifier(i) == m)
+                    .map(|i| attack_types[i])
+                    .collect()
+            };
+
+            let group = Group {
+                army_type: if split % 2 == 0 { ArmyType::Immune } else { ArmyType::Infection },
+                units: next(10_000),
+                hit_points: next(60_000),
+                attack_damage: next(5_000),
+                attack_type: attack_types[next(attack_types.len()) - 1],
+                initiative: next(20),
+                weaknesses: with(1),
+                immunities: with(2),
+            };
+
+            let line = group.to_string();
+            assert_eq!(Group::parse(group.army_type, &line), Ok(group), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_parse_armies() {
+        let (immune_groups, infection_groups) = crate::tests::example_groups();
+        assert_eq!(immune_groups.len(), 2);
+        assert_eq!(infection_groups.len(), 2);
+        assert!(infection_groups.iter().all(|g| g.army_type == ArmyType::Infection));
+
+        // Writing the armies out and reading them back changes nothing, including for the input
+        for input in &[crate::tests::example(), include_str!("input.txt")] {
+            let (immune_groups, infection_groups) = parse_armies(input).unwrap();
+            let output = format_armies(&immune_groups, &infection_groups);
+            assert_eq!(parse_armies(&output), Ok((immune_groups, infection_groups)));
+        }
+    }
+
+    #[test]
+    fn test_parse_armies_errors() {
+        let group = "1 units each with 2 hit points with an attack that does 3 fire damage at initiative 4";
+        assert_eq!(
+            parse_armies(group),
+            Err(ParseError::MissingHeader { line: group.to_string() }),
+        );
+        assert_eq!(
+            parse_armies("Undead:"),
+            Err(ParseError::UnknownHeader { line: "Undead:".to_string() }),
+        );
+        assert_eq!(
+            parse_armies("Infection:\n1 units each with 2 hit points"),
+            Err(ParseError::MalformedGroup { line: "1 units each with 2 hit points".to_string() }),
+        );
+
+        let huge = "1 units each with 99999999999999999999999 hit points with an attack that does 3 fire damage at initiative 4";
+        assert_eq!(
+            parse_armies(&format!("Immune System:\n{}", huge)),
+            Err(ParseError::MalformedGroup { line: huge.to_string() }),
+        );
+
+        let psychic = "1 units each with 2 hit points with an attack that does 3 psychic damage at initiative 4";
+        assert_eq!(
+            Group::parse(ArmyType::Immune, psychic),
+            Err(ParseError::UnknownAttackType {
+                line: psychic.to_string(),
+                attack_type: "psychic".to_string(),
+            }),
+        );
+        let error = Group::parse(ArmyType::Immune, "1 units each with 2 hit points (weak to cold, acid) with an attack that does 3 fire damage at initiative 4").unwrap_err();
+        assert!(matches!(
+            error,
+            ParseError::UnknownAttackType { ref attack_type, .. } if attack_type == "acid"
+        ));
+        assert!(error.to_string().ends_with(": unknown attack type acid"));
+    }
+
     #[test]
     fn test_battle_fight() {
         let (immune_groups, infection_groups) = crate::tests::example_groups();
 
-        let mut battle = Battle {
-            immune_groups,
-            infection_groups,
-        };
+        let mut battle = Battle::new(immune_groups, infection_groups);
 
-        battle.fight();
+        assert_eq!(battle.fight(), Outcome::InfectionWins { units: 5216, rounds: 8 });
 
-        assert_eq!(battle.immune_groups.len(), 0);
-        assert_eq!(battle.infection_groups.len(), 2);
+        assert_eq!(battle.groups(ArmyType::Immune).count(), 0);
+        assert_eq!(battle.groups(ArmyType::Infection).count(), 2);
 
-        let mut unit_counts: Vec<usize> = battle.infection_groups.iter().map(|g| g.units).collect();
+        let mut unit_counts: Vec<usize> =
+            battle.groups(ArmyType::Infection).map(|g| g.units).collect();
         unit_counts.sort();
         assert_eq!(unit_counts, vec![782, 4434]);
     }
+
+    #[test]
+    fn test_battle_fight_logged() {
+        let (immune_groups, infection_groups) = crate::tests::example_groups();
+        let mut battle = Battle::new(immune_groups, infection_groups);
+
+        let mut lines = vec![];
+        battle.fight_logged(&mut |line| lines.push(line.to_string()));
+
+        // The first round, exactly as in the puzzle
+        assert_eq!(
+            lines[..20].to_vec(),
+            vec![
+                "Immune System:",
+                "Group 1 contains 17 units",
+                "Group 2 contains 989 units",
+                "Infection:",
+                "Group 1 contains 801 units",
+                "Group 2 contains 4485 units",
+                "",
+                "Infection group 1 would deal defending group 1 185832 damage",
+                "Infection group 1 would deal defending group 2 185832 damage",
+                "Infection group 2 would deal defending group 2 107640 damage",
+                "Immune System group 1 would deal defending group 1 76619 damage",
+                "Immune System group 1 would deal defending group 2 153238 damage",
+                "Immune System group 2 would deal defending group 1 24725 damage",
+                "",
+                "Infection group 2 attacks defending group 2, killing 84 units",
+                "Immune System group 2 attacks defending group 1, killing 4 units",
+                "Immune System group 1 attacks defending group 2, killing 51 units",
+                "Infection group 1 attacks defending group 1, killing 17 units",
+                "",
+                "Immune System:",
+            ],
+        );
+
+        // And how it ends
+        assert_eq!(
+            lines[lines.len() - 6..].to_vec(),
+            vec![
+                "",
+                "Immune System:",
+                "No groups remain.",
+                "Infection:",
+                "Group 1 contains 782 units",
+                "Group 2 contains 4434 units",
+            ],
+        );
+    }
 }
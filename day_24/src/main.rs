@@ -1,21 +1,27 @@
-#![feature(vec_remove_item)]
-
+mod boost;
 mod groups;
 
-use crate::groups::{Battle, Group, ArmyType};
+use std::env;
+
+use crate::boost::BoostSearch;
+use crate::groups::{format_armies, parse_armies, Battle, Group, Outcome};
 
 fn main() {
-    let immune_groups: Vec<Group> = include_str!("immune.txt")
-        .trim()
-        .split("\n")
-        .map(|s| Group::parse(ArmyType::Immune, s))
-        .collect();
-
-    let infection_groups: Vec<Group> = include_str!("infection.txt")
-        .trim()
-        .split("\n")
-        .map(|s| Group::parse(ArmyType::Infection, s))
-        .collect();
+    let (immune_groups, infection_groups) = match parse_armies(include_str!("input.txt")) {
+        Ok(armies) => armies,
+        Err(error) => return println!("{}", error),
+    };
+
+    // `cargo run -- armies` writes the armies back out, with every group in the same format
+    match env::args().nth(1).as_deref() {
+        Some("armies") => return print!("{}", format_armies(&immune_groups, &infection_groups)),
+        Some("boost") => return boost(&immune_groups, &infection_groups),
+        Some("log") => {
+            let boost = env::args().nth(2).map_or(0, |boost| boost.parse().unwrap());
+            return log(immune_groups, infection_groups, boost);
+        },
+        _ => (),
+    }
 
     println!("part_1: {}", part_1(immune_groups.clone(), infection_groups.clone()));
     println!("part_2: {}", part_2(immune_groups.clone(), infection_groups.clone()));
@@ -23,66 +29,89 @@ fn main() {
 
 // How many units does the winning army have?
 fn part_1(immune_groups: Vec<Group>, infection_groups: Vec<Group>) -> usize {
-    let mut battle = Battle {
-        immune_groups,
-        infection_groups,
-    };
-
-    battle.fight();
+    let mut battle = Battle::new(immune_groups, infection_groups);
 
-    if battle.immune_groups.len() > 0 {
-        battle.immune_groups.iter().map(|g| g.units).sum()
-    } else {
-        battle.infection_groups.iter().map(|g| g.units).sum()
+    match battle.fight() {
+        Outcome::ImmuneWins { units, .. } | Outcome::InfectionWins { units, .. } => units,
+        Outcome::Stalemate { .. } => panic!("Neither army can win"),
     }
 }
 
 // How many units does the immune system have left after getting the smallest boost it needs to win?
 fn part_2(immune_groups: Vec<Group>, infection_groups: Vec<Group>) -> usize {
-    // The battle doesn't terminate at boost 45 because the final Immune group can't deal enough
-    // damage to kill ANY Infection units, and the Immune group is immune to the Infection attack
-    // type. So we just skip to boost 46, which gives us the answer.
-    let mut boost = 46;
-
-    loop {
-        let mut immune_groups = immune_groups.clone();
-        immune_groups.iter_mut().for_each(|g| g.attack_damage += boost);
-
-        let mut battle = Battle {
-            immune_groups,
-            infection_groups: infection_groups.clone(),
-        };
-
-        battle.fight();
-
-        if battle.immune_groups.len() > 0 {
-            return battle.immune_groups.iter().map(|g| g.units).sum();
-        } else {
-            boost += 1;
-        }
+    let mut search = BoostSearch::new(&immune_groups, &infection_groups);
+    let (_boost, units) = search.smallest_winning_boost().expect("No boost wins");
+    units
+}
+
+// `cargo run -- boost` shows how hard the search for the smallest boost had to work
+fn boost(immune_groups: &[Group], infection_groups: &[Group]) {
+    let mut search = BoostSearch::new(immune_groups, infection_groups);
+    match search.smallest_winning_boost() {
+        Some((boost, units)) => println!("Boost {} wins with {} units left", boost, units),
+        None => println!("No boost wins"),
     }
+    println!("{}", search.stats);
+}
+
+// `cargo run -- log [boost]` prints the whole battle round by round
+fn log(mut immune_groups: Vec<Group>, infection_groups: Vec<Group>, boost: usize) {
+    immune_groups.iter_mut().for_each(|g| g.attack_damage += boost);
+
+    let mut battle = Battle::new(immune_groups, infection_groups);
+    let outcome = battle.fight_logged(&mut |line| println!("{}", line));
+    println!("\n{:?}", outcome);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::boost::fight_boosted;
+    use crate::groups::ArmyType;
+
+    pub fn example() -> &'static str {
+        "Immune System:
+17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does 4507 fire damage at initiative 2
+989 units each with 1274 hit points (immune to fire; weak to bludgeoning, slashing) with an attack that does 25 slashing damage at initiative 3
+
+Infection:
+801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1
+4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4
+"
+    }
 
     pub fn example_groups() -> (Vec<Group>, Vec<Group>) {
-        let immune_groups = vec![
-            "17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does 4507 fire damage at initiative 2",
-            "989 units each with 1274 hit points (immune to fire; weak to bludgeoning, slashing) with an attack that does 25 slashing damage at initiative 3",
-        ].iter().map(|s| Group::parse(ArmyType::Immune, s)).collect();
-
-        let infection_groups = vec![
-            "801 units each with 4706 hit points (weak to radiation) with an attack that does 116 bludgeoning damage at initiative 1",
-            "4485 units each with 2961 hit points (immune to radiation; weak to fire, cold) with an attack that does 12 slashing damage at initiative 4",
-        ].iter().map(|s| Group::parse(ArmyType::Infection, s)).collect();
-
-        (immune_groups, infection_groups)
+        parse_armies(example()).unwrap()
     }
 
     #[test]
     fn test_part_1() {
+        let (immune_groups, infection_groups) = example_groups();
+        assert_eq!(part_1(immune_groups, infection_groups), 5216);
+    }
+
+    #[test]
+    fn test_fight_boosted() {
+        let (immune_groups, infection_groups) = example_groups();
+        assert_eq!(
+            fight_boosted(&immune_groups, &infection_groups, 1570),
+            Outcome::ImmuneWins { units: 51, rounds: 41 },
+        );
+
+        // The immune group left standing is immune to fire, and the infection group left
+        // standing is immune to radiation, so they can't hurt each other
+        let stalemate = Group::parse(
+            ArmyType::Immune,
+            "10 units each with 10 hit points (immune to fire) with an attack that does 5 radiation damage at initiative 2",
+        ).unwrap();
+        let infection = Group::parse(
+            ArmyType::Infection,
+            "10 units each with 10 hit points (immune to radiation) with an attack that does 5 fire damage at initiative 1",
+        ).unwrap();
+        assert_eq!(
+            fight_boosted(&[stalemate], &[infection], 0),
+            Outcome::Stalemate { immune_units: 10, infection_units: 10, rounds: 1 },
+        );
     }
 
     #[test]